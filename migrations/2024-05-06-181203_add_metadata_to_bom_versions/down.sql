@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE bom_versions
+    DROP COLUMN author,
+    DROP COLUMN message,
+    DROP COLUMN ticket;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+ALTER TABLE bom_versions
+    ADD COLUMN author VARCHAR,
+    ADD COLUMN message TEXT,
+    ADD COLUMN ticket VARCHAR;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+CREATE TABLE bom_snapshots (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    bom_id UUID NOT NULL,
+    version INTEGER NOT NULL,
+    snapshot JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (bom_id) REFERENCES boms(id) ON DELETE CASCADE,
+    UNIQUE (bom_id, version)
+);
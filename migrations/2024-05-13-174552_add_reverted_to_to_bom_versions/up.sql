@@ -0,0 +1,3 @@
+-- Your SQL goes here
+
+ALTER TABLE bom_versions ADD COLUMN reverted_to INTEGER;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+UPDATE bom_versions SET version = 0 WHERE version = 1;
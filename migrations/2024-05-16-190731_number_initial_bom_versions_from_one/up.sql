@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- The version created together with a BOM used to be stored as version 0 while
+-- the BOM itself starts at version 1
+UPDATE bom_versions SET version = 1 WHERE version = 0;
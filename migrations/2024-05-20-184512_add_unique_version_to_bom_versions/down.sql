@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE bom_versions DROP CONSTRAINT bom_versions_bom_id_version_key;
@@ -0,0 +1,46 @@
+-- Your SQL goes here
+
+-- Concurrent writers could store the same version of a BOM twice. The versions
+-- of such BOMs are renumbered in the order they were written before the
+-- constraint is added
+CREATE TEMPORARY TABLE renumbered_bom_versions AS
+SELECT
+    id,
+    bom_id,
+    version AS old_version,
+    ROW_NUMBER() OVER (PARTITION BY bom_id ORDER BY version, created_at, id) AS new_version
+FROM bom_versions
+WHERE bom_id IN (
+    SELECT bom_id FROM bom_versions GROUP BY bom_id, version HAVING COUNT(*) > 1
+);
+
+-- A revert restored the state after the last of the duplicates
+UPDATE bom_versions
+SET reverted_to = (
+    SELECT MAX(r.new_version)
+    FROM renumbered_bom_versions r
+    WHERE r.bom_id = bom_versions.bom_id AND r.old_version = bom_versions.reverted_to
+)
+WHERE reverted_to IS NOT NULL
+    AND bom_id IN (SELECT bom_id FROM renumbered_bom_versions);
+
+UPDATE bom_versions
+SET version = r.new_version
+FROM renumbered_bom_versions r
+WHERE bom_versions.id = r.id;
+
+-- Snapshots of renumbered BOMs no longer match their version and are rebuilt
+-- from the events
+DELETE FROM bom_snapshots
+WHERE bom_id IN (SELECT bom_id FROM renumbered_bom_versions);
+
+UPDATE boms
+SET version = (
+    SELECT MAX(r.new_version) FROM renumbered_bom_versions r WHERE r.bom_id = boms.id
+)
+WHERE id IN (SELECT bom_id FROM renumbered_bom_versions);
+
+DROP TABLE renumbered_bom_versions;
+
+ALTER TABLE bom_versions
+    ADD CONSTRAINT bom_versions_bom_id_version_key UNIQUE (bom_id, version);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE bom_versions
+    DROP CONSTRAINT bom_versions_bom_id_tag_key,
+    DROP COLUMN squashed_from,
+    DROP COLUMN tag;
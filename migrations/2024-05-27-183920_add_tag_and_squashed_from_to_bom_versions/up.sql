@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+ALTER TABLE bom_versions
+    ADD COLUMN tag VARCHAR(64),
+    ADD COLUMN squashed_from INTEGER,
+    ADD CONSTRAINT bom_versions_bom_id_tag_key UNIQUE (bom_id, tag);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+-- The source is deliberately not a foreign key so that the lineage of a clone
+-- survives the removal of the BOM it was cloned from
+CREATE TABLE bom_lineage (
+    bom_id UUID PRIMARY KEY,
+    source_bom_id UUID NOT NULL,
+    source_version INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (bom_id) REFERENCES boms(id) ON DELETE CASCADE
+);
+
+CREATE INDEX bom_lineage_source_bom_id_idx ON bom_lineage (source_bom_id);
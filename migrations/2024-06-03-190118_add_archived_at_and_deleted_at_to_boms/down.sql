@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE boms
+    DROP COLUMN deleted_at,
+    DROP COLUMN archived_at;
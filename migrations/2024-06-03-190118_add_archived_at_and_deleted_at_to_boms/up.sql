@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE boms
+    ADD COLUMN archived_at TIMESTAMPTZ,
+    ADD COLUMN deleted_at TIMESTAMPTZ;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE bom_versions
+    DROP COLUMN schema_version;
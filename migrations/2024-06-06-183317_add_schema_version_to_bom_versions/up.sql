@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE bom_versions
+    ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE boms
+    DROP COLUMN rule_set,
+    DROP COLUMN category;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE boms
+    ADD COLUMN category VARCHAR,
+    ADD COLUMN rule_set VARCHAR;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+-- Components without a row are active
+CREATE TABLE component_lifecycles (
+    component_id UUID PRIMARY KEY,
+    status VARCHAR NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (component_id) REFERENCES components(id) ON DELETE CASCADE
+);
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+ALTER TABLE boms_components
+    ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+
+UPDATE boms_components
+SET position = numbered.position
+FROM (
+    SELECT bom_id,
+           component_id,
+           ROW_NUMBER() OVER (PARTITION BY bom_id ORDER BY ctid) - 1 AS position
+    FROM boms_components
+) AS numbered
+WHERE boms_components.bom_id = numbered.bom_id
+  AND boms_components.component_id = numbered.component_id;
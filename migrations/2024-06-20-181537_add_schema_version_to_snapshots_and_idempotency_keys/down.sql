@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE idempotency_keys
+    DROP COLUMN schema_version;
+
+ALTER TABLE bom_snapshots
+    DROP COLUMN schema_version;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+
+ALTER TABLE bom_snapshots
+    ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE idempotency_keys
+    ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 1;
@@ -1,19 +1,25 @@
-use std::env::current_dir;
+use std::{collections::HashMap, env::current_dir};
 
 use config::{Config, ConfigError, File};
 use secrecy::{ExposeSecret, Secret};
 use serde::Deserialize;
 
+use crate::domain::rules::Rule;
+
 #[derive(Deserialize)]
 pub struct Settings {
     pub app: AppSettings,
     pub db: DbSettings,
+    pub bom: BomSettings,
 }
 
 #[derive(Deserialize)]
 pub struct AppSettings {
     pub port: u16,
     pub host: String,
+    /// Bearer token required by the `/admin` routes. They are closed when no
+    /// token is configured.
+    pub admin_token: Option<Secret<String>>,
 }
 
 #[derive(Deserialize)]
@@ -48,6 +54,52 @@ impl DbSettings {
     }
 }
 
+#[derive(Deserialize, Clone)]
+pub struct BomSettings {
+    /// Store a materialized snapshot every `snapshot_interval` versions.
+    /// A value of 0 disables snapshots.
+    pub snapshot_interval: i32,
+    /// How long a response is kept for replays of the same `Idempotency-Key`.
+    pub idempotency_retention_hours: i64,
+    /// How long a request may hold its `Idempotency-Key` without storing a
+    /// response before a retry takes the key over.
+    pub idempotency_reservation_timeout_seconds: i64,
+    /// How long a deleted BOM can be restored before it may be purged.
+    pub restore_window_days: i64,
+    pub rules: RuleSettings,
+}
+
+/// Validation rules, read from `rules.yaml`.
+#[derive(Deserialize, Clone)]
+pub struct RuleSettings {
+    pub default_rule_set: String,
+    pub rule_sets: HashMap<String, Vec<Rule>>,
+    /// The rule set of each BOM category.
+    #[serde(default)]
+    pub categories: HashMap<String, String>,
+}
+
+impl RuleSettings {
+    /// Picks the rule set of the category of the BOM, then its own one, then
+    /// the default one.
+    pub fn rule_set_name<'a>(
+        &'a self,
+        rule_set: Option<&'a str>,
+        category: Option<&str>,
+    ) -> &'a str {
+        self.category_rule_set(category)
+            .or(rule_set)
+            .unwrap_or(&self.default_rule_set)
+    }
+
+    /// The rule set every BOM of `category` is bound to, if any.
+    pub fn category_rule_set(&self, category: Option<&str>) -> Option<&str> {
+        category
+            .and_then(|category| self.categories.get(category))
+            .map(String::as_str)
+    }
+}
+
 pub fn get_config() -> Result<Settings, ConfigError> {
     let base_path = current_dir().expect("Failed to determine current directory");
     let config_dir = base_path.join("configuration");
@@ -61,7 +113,14 @@ pub fn get_config() -> Result<Settings, ConfigError> {
 
     let settings: Config = Config::builder()
         .add_source(File::from(config_dir.join("base.yaml")))
+        .add_source(File::from(config_dir.join("rules.yaml")))
         .add_source(File::from(config_dir.join(environment_filename)))
+        // e.g. `APP_APP__ADMIN_TOKEN` sets `app.admin_token`
+        .add_source(
+            config::Environment::with_prefix("APP")
+                .prefix_separator("_")
+                .separator("__"),
+        )
         .build()?;
     settings.try_deserialize::<Settings>()
 }
@@ -3,3 +3,13 @@ pub enum DomainError {
     ConversionError(String),
     ValidationError(String),
 }
+
+impl DomainError {
+    pub fn into_message(self) -> String {
+        match self {
+            DomainError::ValidationError(message) | DomainError::ConversionError(message) => {
+                message
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 pub mod error;
 pub mod models;
 pub mod newtypes;
+pub mod rules;
+pub mod upcasting;
 pub mod validation;
 
 pub use models::*;
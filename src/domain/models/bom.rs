@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -15,6 +17,14 @@ pub struct BOM {
     pub components: Vec<CountedComponent>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Archived BOMs stay readable but no longer accept changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Rule set of a BOM whose category has none of its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_set: Option<String>,
 }
 
 impl Default for BOM {
@@ -27,6 +37,9 @@ impl Default for BOM {
             components: Vec::new(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            archived_at: None,
+            category: None,
+            rule_set: None,
         }
     }
 }
@@ -45,6 +58,9 @@ impl BOM {
             BOMChangeEvent::DescriptionChanged(description) => {
                 self.description = Some(description.clone());
             }
+            BOMChangeEvent::DescriptionCleared => {
+                self.description = None;
+            }
             BOMChangeEvent::ComponentAdded(component, qty) => {
                 self.components
                     .push(CountedComponent::new(component.clone(), *qty));
@@ -59,6 +75,16 @@ impl BOM {
                     }
                 });
             }
+            BOMChangeEvent::ComponentReplaced { old, new, qty } => {
+                if let Some(cc) = self
+                    .components
+                    .iter_mut()
+                    .find(|cc| cc.component.id == old.id)
+                {
+                    cc.component = new.clone();
+                    cc.quantity = *qty;
+                }
+            }
         }
         Ok(())
     }
@@ -71,6 +97,40 @@ impl BOM {
         self.description = None;
         self.components.clear();
     }
+
+    /// Replaces name, description and lines with the ones of `other` while
+    /// keeping the identity and timestamps of this BOM.
+    pub fn restore_content_from(&mut self, other: BOM) {
+        self.name = other.name;
+        self.description = other.description;
+        self.components = other.components;
+    }
+
+    /// The summed up price of every line, per currency.
+    pub fn total_cost(&self) -> BTreeMap<String, f32> {
+        let mut totals = BTreeMap::new();
+        for cc in self.components.iter() {
+            *totals
+                .entry(cc.component.price.currency.clone())
+                .or_default() += cc.component.price.value * cc.quantity as f32;
+        }
+        totals
+    }
+
+    /// Compares name, description, version and lines while ignoring line order
+    /// and the bookkeeping fields (id and timestamps).
+    pub fn has_same_content(&self, other: &BOM) -> bool {
+        let sorted_components = |bom: &BOM| {
+            let mut components = bom.components.clone();
+            components.sort_by_key(|cc| cc.component.id);
+            components
+        };
+
+        self.name == other.name
+            && self.description == other.description
+            && self.version == other.version
+            && sorted_components(self) == sorted_components(other)
+    }
 }
 
 #[cfg(test)]
@@ -78,10 +138,7 @@ mod tests {
     use mockall::{mock, predicate::*};
 
     use super::*;
-    use crate::domain::{
-        newtypes::new_bom::{self, NewBOM},
-        BOMChangeEvent, Component, Price,
-    };
+    use crate::domain::{BOMChangeEvent, Component, Price};
 
     mock! {
         pub BOMChangeEventValidator {}
@@ -235,6 +292,37 @@ mod tests {
         assert!(bom.components.is_empty());
     }
 
+    #[test]
+    fn test_apply_change_component_replaced_keeps_line_position() {
+        let mut bom = setup_test_bom();
+        let mut mock_validator = MockBOMChangeEventValidator::new();
+
+        let old = bom.components[0].component.clone();
+        let last = create_test_component();
+        bom.components.push(CountedComponent::new(last.clone(), 1));
+        let new = create_test_component();
+
+        mock_validator
+            .expect_validate()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let event = BOMChangeEvent::ComponentReplaced {
+            old,
+            new: new.clone(),
+            qty: 3,
+        };
+        let _ = bom.apply_change(&event, mock_validator);
+
+        assert_eq!(
+            bom.components,
+            vec![
+                CountedComponent::new(new, 3),
+                CountedComponent::new(last, 1)
+            ]
+        );
+    }
+
     #[test]
     fn test_apply_change_component_updated() {
         let mut bom = setup_test_bom();
@@ -272,6 +360,35 @@ mod tests {
         assert_eq!(bom.components[0].quantity, 1);
     }
 
+    #[test]
+    fn test_has_same_content_ignores_line_order_and_timestamps() {
+        let bom = setup_test_bom();
+        let mut other = bom.clone();
+
+        other
+            .components
+            .push(CountedComponent::new(create_test_component(), 2));
+        other.components.reverse();
+        other.id = Uuid::new_v4();
+        other.updated_at = Utc::now();
+
+        let mut expected = bom.clone();
+        expected.components = other.components.iter().rev().cloned().collect();
+
+        assert!(expected.has_same_content(&other));
+        assert!(!bom.has_same_content(&other));
+    }
+
+    #[test]
+    fn test_has_same_content_detects_quantity_change() {
+        let bom = setup_test_bom();
+        let mut other = bom.clone();
+
+        other.components[0].quantity = 5;
+
+        assert!(!bom.has_same_content(&other));
+    }
+
     #[test]
     fn test_try_from_bom() {
         let component = create_test_component();
@@ -281,9 +398,7 @@ mod tests {
             BOMChangeEvent::ComponentAdded(component.clone(), 1),
         ];
 
-        let new_bom = new_bom::NewBOM { events };
-
-        let bom = BOM::try_from(&new_bom).unwrap();
+        let bom = BOM::try_from(events.as_slice()).unwrap();
 
         assert_eq!(bom.name, "Test BOM");
         assert_eq!(bom.description, Some("Test Description".to_string()));
@@ -294,11 +409,9 @@ mod tests {
 
     #[test]
     fn test_try_from_bom_with_empty_events() {
-        let events = vec![];
-
-        let new_bom = new_bom::NewBOM { events };
+        let events: Vec<BOMChangeEvent> = vec![];
 
-        let result = BOM::try_from(&new_bom);
+        let result = BOM::try_from(events.as_slice());
 
         assert!(result.is_err());
         assert_eq!(
@@ -315,9 +428,7 @@ mod tests {
             BOMChangeEvent::ComponentAdded(component.clone(), 1),
         ];
 
-        let new_bom = new_bom::NewBOM { events };
-
-        let result = BOM::try_from(&new_bom);
+        let result = BOM::try_from(events.as_slice());
 
         assert!(result.is_err());
         assert_eq!(
@@ -345,9 +456,7 @@ mod tests {
         )
         .collect();
 
-        let new_bom = NewBOM { events: events };
-
-        let bom = BOM::try_from(&new_bom).unwrap();
+        let bom = BOM::try_from(events.as_slice()).unwrap();
 
         assert_eq!(bom.components.len(), 5);
         for (i, counted_component) in bom.components.iter().enumerate() {
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::{
+    error::DomainError, validation::BOMChangeEventValidator, BOMChangeEvent, BomVersion,
+    CountedComponent, BOM,
+};
+
+/// The version in which something happened, and when.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VersionStamp {
+    pub version: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&BomVersion> for VersionStamp {
+    fn from(value: &BomVersion) -> Self {
+        Self {
+            version: value.version,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlamedValue {
+    pub value: String,
+    pub last_changed: VersionStamp,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlamedLine {
+    #[serde(flatten)]
+    pub line: CountedComponent,
+    pub introduced: VersionStamp,
+    pub last_changed: VersionStamp,
+}
+
+/// A BOM at a given version where the name, description and every line are
+/// annotated with the versions that produced them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BOMBlame {
+    pub bom_id: Uuid,
+    pub version: i32,
+    pub name: BlamedValue,
+    pub description: Option<BlamedValue>,
+    pub components: Vec<BlamedLine>,
+}
+
+impl BOMBlame {
+    /// Replays `versions`, which must start at the first version of the BOM,
+    /// on top of the emptied `bom`.
+    pub fn from_versions(mut bom: BOM, versions: &[BomVersion]) -> Result<Self, DomainError> {
+        let first = versions
+            .first()
+            .map(VersionStamp::from)
+            .ok_or_else(|| DomainError::ValidationError("BOM has no versions".to_string()))?;
+
+        bom.clean_for_revert();
+
+        let mut name_changed = first;
+        let mut description_changed = first;
+        let mut lines: HashMap<Uuid, (VersionStamp, VersionStamp)> = HashMap::new();
+
+        for version in versions.iter() {
+            let stamp = VersionStamp::from(version);
+
+            for event in version.changes.iter() {
+                bom.apply_change(event, BOMChangeEventValidator)?;
+
+                match event {
+                    BOMChangeEvent::NameChanged(_) => name_changed = stamp,
+                    BOMChangeEvent::DescriptionChanged(_) | BOMChangeEvent::DescriptionCleared => {
+                        description_changed = stamp
+                    }
+                    BOMChangeEvent::ComponentAdded(component, _) => {
+                        lines.insert(component.id, (stamp, stamp));
+                    }
+                    BOMChangeEvent::ComponentUpdated(id, _) => {
+                        if let Some((_, last_changed)) = lines.get_mut(id) {
+                            *last_changed = stamp;
+                        }
+                    }
+                    BOMChangeEvent::ComponentRemoved(component) => {
+                        lines.remove(&component.id);
+                    }
+                    BOMChangeEvent::ComponentReplaced { old, new, .. } => {
+                        let introduced = lines
+                            .remove(&old.id)
+                            .map_or(stamp, |(introduced, _)| introduced);
+                        lines.insert(new.id, (introduced, stamp));
+                    }
+                }
+            }
+        }
+
+        let components = bom
+            .components
+            .into_iter()
+            .map(|line| {
+                let (introduced, last_changed) = lines
+                    .get(&line.component.id)
+                    .copied()
+                    .unwrap_or((first, first));
+                BlamedLine {
+                    line,
+                    introduced,
+                    last_changed,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            bom_id: bom.id,
+            version: versions.last().map_or(first.version, |v| v.version),
+            name: BlamedValue {
+                value: bom.name,
+                last_changed: name_changed,
+            },
+            description: bom.description.map(|value| BlamedValue {
+                value,
+                last_changed: description_changed,
+            }),
+            components,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{Component, Price, VersionMetadata};
+
+    use super::*;
+
+    fn create_test_component(part_number: &str) -> Component {
+        Component {
+            id: Uuid::new_v4(),
+            name: "Component".to_string(),
+            part_number: part_number.to_string(),
+            description: None,
+            supplier: "Supplier".to_string(),
+            price: Price {
+                value: 10.0,
+                currency: "USD".to_string(),
+            },
+        }
+    }
+
+    fn create_version(bom_id: &Uuid, version: i32, changes: Vec<BOMChangeEvent>) -> BomVersion {
+        BomVersion::new(
+            bom_id,
+            version,
+            Box::new(changes),
+            VersionMetadata::default(),
+        )
+    }
+
+    #[test]
+    fn test_blame_tracks_introduced_and_last_changed_versions() {
+        let bom = BOM::default();
+        let first = create_test_component("1");
+        let second = create_test_component("2");
+        let removed = create_test_component("3");
+
+        let versions = vec![
+            create_version(
+                &bom.id,
+                1,
+                vec![
+                    BOMChangeEvent::NameChanged("BOM".to_string()),
+                    BOMChangeEvent::ComponentAdded(first.clone(), 1),
+                    BOMChangeEvent::ComponentAdded(removed.clone(), 1),
+                ],
+            ),
+            create_version(
+                &bom.id,
+                2,
+                vec![
+                    BOMChangeEvent::DescriptionChanged("Description".to_string()),
+                    BOMChangeEvent::ComponentAdded(second.clone(), 4),
+                    BOMChangeEvent::ComponentRemoved(removed),
+                ],
+            ),
+            create_version(
+                &bom.id,
+                3,
+                vec![BOMChangeEvent::ComponentUpdated(first.id, 2)],
+            ),
+        ];
+
+        let blame = BOMBlame::from_versions(bom, &versions).unwrap();
+
+        assert_eq!(blame.version, 3);
+        assert_eq!(blame.name.value, "BOM");
+        assert_eq!(blame.name.last_changed.version, 1);
+        assert_eq!(blame.description.as_ref().unwrap().last_changed.version, 2);
+        assert_eq!(blame.components.len(), 2);
+
+        let first_line = &blame.components[0];
+        assert_eq!(first_line.line.component, first);
+        assert_eq!(first_line.line.quantity, 2);
+        assert_eq!(first_line.introduced.version, 1);
+        assert_eq!(first_line.last_changed.version, 3);
+
+        let second_line = &blame.components[1];
+        assert_eq!(second_line.line.component, second);
+        assert_eq!(second_line.introduced.version, 2);
+        assert_eq!(second_line.last_changed.version, 2);
+    }
+
+    #[test]
+    fn test_blame_without_versions_fails() {
+        assert!(BOMBlame::from_versions(BOM::default(), &[]).is_err());
+    }
+}
@@ -8,6 +8,7 @@ use super::{BOMDiff, Component, BOM};
 pub trait BOMChangeEventVisitor {
     fn visit_name_changed(&mut self, name: &str, bom: &BOM, diff: &mut BOMDiff);
     fn visit_description_changed(&mut self, description: &str, bom: &BOM, diff: &mut BOMDiff);
+    fn visit_description_cleared(&mut self, bom: &BOM, diff: &mut BOMDiff);
     fn visit_component_added(
         &mut self,
         component: &Component,
@@ -23,6 +24,14 @@ pub trait BOMChangeEventVisitor {
         diff: &mut BOMDiff,
     );
     fn visit_component_removed(&mut self, component: &Component, diff: &mut BOMDiff);
+    fn visit_component_replaced(
+        &mut self,
+        old: &Component,
+        new: &Component,
+        qty: i32,
+        bom: &BOM,
+        diff: &mut BOMDiff,
+    );
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -30,9 +39,16 @@ pub trait BOMChangeEventVisitor {
 pub enum BOMChangeEvent {
     NameChanged(String),
     DescriptionChanged(String),
+    DescriptionCleared,
     ComponentAdded(Component, i32),
     ComponentRemoved(Component),
     ComponentUpdated(Uuid, i32),
+    /// Swaps the component of a line for another one in place.
+    ComponentReplaced {
+        old: Component,
+        new: Component,
+        qty: i32,
+    },
 }
 
 impl BOMChangeEvent {
@@ -42,6 +58,7 @@ impl BOMChangeEvent {
             BOMChangeEvent::DescriptionChanged(description) => {
                 visitor.visit_description_changed(description, bom, diff)
             }
+            BOMChangeEvent::DescriptionCleared => visitor.visit_description_cleared(bom, diff),
             BOMChangeEvent::ComponentAdded(component, qty) => {
                 visitor.visit_component_added(component, *qty, bom, diff)
             }
@@ -51,8 +68,21 @@ impl BOMChangeEvent {
             BOMChangeEvent::ComponentRemoved(component) => {
                 visitor.visit_component_removed(component, diff)
             }
+            BOMChangeEvent::ComponentReplaced { old, new, qty } => {
+                visitor.visit_component_replaced(old, new, *qty, bom, diff)
+            }
         }
     }
+
+    /// Whether the event changes the header of the BOM rather than its lines.
+    pub fn is_header_change(&self) -> bool {
+        matches!(
+            self,
+            BOMChangeEvent::NameChanged(_)
+                | BOMChangeEvent::DescriptionChanged(_)
+                | BOMChangeEvent::DescriptionCleared
+        )
+    }
 }
 
 impl Display for BOMChangeEvent {
@@ -62,6 +92,7 @@ impl Display for BOMChangeEvent {
             BOMChangeEvent::DescriptionChanged(description) => {
                 write!(f, "DescriptionChanged({})", description)
             }
+            BOMChangeEvent::DescriptionCleared => write!(f, "DescriptionCleared"),
             BOMChangeEvent::ComponentAdded(component, qty) => {
                 write!(f, "ComponentAdded({}, {})", component.name, qty)
             }
@@ -71,6 +102,9 @@ impl Display for BOMChangeEvent {
             BOMChangeEvent::ComponentUpdated(id, qty) => {
                 write!(f, "ComponentUpdated({}, {})", id, qty)
             }
+            BOMChangeEvent::ComponentReplaced { old, new, qty } => {
+                write!(f, "ComponentReplaced({}, {}, {})", old.name, new.name, qty)
+            }
         }
     }
 }
@@ -91,6 +125,7 @@ mod tests {
         impl BOMChangeEventVisitor for BOMDiffVisitor {
             fn visit_name_changed(&mut self, name: &str, bom: &BOM, diff: &mut BOMDiff);
             fn visit_description_changed(&mut self, description: &str, bom: &BOM, diff: &mut BOMDiff);
+            fn visit_description_cleared(&mut self, bom: &BOM, diff: &mut BOMDiff);
             fn visit_component_added(
                 &mut self,
                 component: &Component,
@@ -106,6 +141,14 @@ mod tests {
                 diff: &mut BOMDiff,
             );
             fn visit_component_removed(&mut self, component: &Component, diff: &mut BOMDiff);
+            fn visit_component_replaced(
+                &mut self,
+                old: &Component,
+                new: &Component,
+                qty: i32,
+                bom: &BOM,
+                diff: &mut BOMDiff,
+            );
         }
     }
 
@@ -149,6 +192,21 @@ mod tests {
         event.accept(&mut visitor, &bom, &mut diff);
     }
 
+    #[test]
+    fn test_description_cleared_event() {
+        let mut visitor = MockBOMDiffVisitor::new();
+        let mut diff = BOMDiff::default();
+        let bom = BOM::default();
+
+        visitor
+            .expect_visit_description_cleared()
+            .times(1)
+            .returning(|_, _| {});
+
+        let event = BOMChangeEvent::DescriptionCleared;
+        event.accept(&mut visitor, &bom, &mut diff);
+    }
+
     #[test]
     fn test_component_added_event() {
         let mut visitor = MockBOMDiffVisitor::new();
@@ -229,4 +287,45 @@ mod tests {
         let event = BOMChangeEvent::ComponentRemoved(component);
         event.accept(&mut visitor, &bom, &mut diff);
     }
+
+    #[test]
+    fn test_component_replaced_event() {
+        let mut visitor = MockBOMDiffVisitor::new();
+        let mut diff = BOMDiff::default();
+        let bom = BOM::default();
+        let component = Component {
+            id: Uuid::new_v4(),
+            name: "Component".to_string(),
+            description: Some("Description".to_string()),
+            supplier: "Supplier".to_string(),
+            part_number: "12345".to_string(),
+            price: Price {
+                value: 10.0,
+                currency: "USD".to_string(),
+            },
+        };
+        let replacement = Component {
+            id: Uuid::new_v4(),
+            ..component.clone()
+        };
+
+        visitor
+            .expect_visit_component_replaced()
+            .with(
+                predicate::eq(component.clone()),
+                predicate::eq(replacement.clone()),
+                predicate::eq(10),
+                predicate::always(),
+                predicate::always(),
+            )
+            .times(1)
+            .returning(|_, _, _, _, _| {});
+
+        let event = BOMChangeEvent::ComponentReplaced {
+            old: component,
+            new: replacement,
+            qty: 10,
+        };
+        event.accept(&mut visitor, &bom, &mut diff);
+    }
 }
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::BOM;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BomSnapshot {
+    pub id: Uuid,
+    pub bom_id: Uuid,
+    pub version: i32,
+    pub bom: BOM,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BomSnapshot {
+    pub fn new(bom: &BOM) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            bom_id: bom.id,
+            version: bom.version,
+            bom: bom.clone(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Outcome of comparing every stored snapshot of a BOM against a full replay
+/// of its event log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotConsistency {
+    pub bom_id: Uuid,
+    pub consistent: bool,
+    pub checked_versions: Vec<i32>,
+    pub mismatched_versions: Vec<i32>,
+}
+
+impl SnapshotConsistency {
+    pub fn new(bom_id: Uuid, checked_versions: Vec<i32>, mismatched_versions: Vec<i32>) -> Self {
+        Self {
+            bom_id,
+            consistent: mismatched_versions.is_empty(),
+            checked_versions,
+            mismatched_versions,
+        }
+    }
+}
@@ -1,7 +1,15 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::domain::BOMChangeEvent;
+use crate::domain::{BOMChangeEvent, BOM};
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct VersionMetadata {
+    pub author: Option<String>,
+    pub message: Option<String>,
+    pub ticket: Option<String>,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BomVersion {
@@ -9,17 +17,181 @@ pub struct BomVersion {
     pub bom_id: Uuid,
     pub version: i32,
     pub changes: Box<Vec<BOMChangeEvent>>,
+    pub metadata: VersionMetadata,
+    pub reverted_to: Option<i32>,
+    pub tag: Option<String>,
+    /// First version of the range that was squashed into this one.
+    pub squashed_from: Option<i32>,
     pub created_at: DateTime<Utc>,
 }
 
 impl BomVersion {
-    pub fn new(bom_id: &Uuid, version: i32, changes: Box<Vec<BOMChangeEvent>>) -> Self {
+    pub fn new(
+        bom_id: &Uuid,
+        version: i32,
+        changes: Box<Vec<BOMChangeEvent>>,
+        metadata: VersionMetadata,
+    ) -> Self {
         Self {
             id: Uuid::new_v4(),
             bom_id: *bom_id,
             version,
             changes,
+            metadata,
+            reverted_to: None,
+            tag: None,
+            squashed_from: None,
             created_at: Utc::now(),
         }
     }
+
+    pub fn as_revert_of(mut self, version: i32) -> Self {
+        self.reverted_to = Some(version);
+        self
+    }
+
+    /// Replaces the events of this version with `changes`, which cover every
+    /// version from `from` up to this one.
+    pub fn as_squash_of(mut self, from: i32, changes: Vec<BOMChangeEvent>) -> Self {
+        self.changes = Box::new(changes);
+        self.squashed_from = Some(from);
+        self
+    }
+}
+
+/// A BOM together with the metadata of the version it represents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionedBOM {
+    #[serde(flatten)]
+    pub bom: BOM,
+    #[serde(flatten)]
+    pub metadata: VersionMetadata,
+}
+
+impl VersionedBOM {
+    pub fn new(bom: BOM, metadata: VersionMetadata) -> Self {
+        Self { bom, metadata }
+    }
+}
+
+/// One entry of the version history of a BOM.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionSummary {
+    pub version: i32,
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub metadata: VersionMetadata,
+    pub reverted_to: Option<i32>,
+    pub tag: Option<String>,
+    pub squashed_from: Option<i32>,
+    pub changes: Vec<BOMChangeEvent>,
+    pub summary: Vec<String>,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub lines_updated: usize,
+    pub lines_replaced: usize,
+}
+
+impl From<BomVersion> for VersionSummary {
+    fn from(value: BomVersion) -> Self {
+        let count = |predicate: fn(&BOMChangeEvent) -> bool| {
+            value
+                .changes
+                .iter()
+                .filter(|event| predicate(event))
+                .count()
+        };
+
+        Self {
+            version: value.version,
+            created_at: value.created_at,
+            summary: value
+                .changes
+                .iter()
+                .map(|event| event.to_string())
+                .collect(),
+            lines_added: count(|event| matches!(event, BOMChangeEvent::ComponentAdded(..))),
+            lines_removed: count(|event| matches!(event, BOMChangeEvent::ComponentRemoved(..))),
+            lines_updated: count(|event| matches!(event, BOMChangeEvent::ComponentUpdated(..))),
+            lines_replaced: count(|event| {
+                matches!(event, BOMChangeEvent::ComponentReplaced { .. })
+            }),
+            metadata: value.metadata,
+            reverted_to: value.reverted_to,
+            tag: value.tag,
+            squashed_from: value.squashed_from,
+            changes: *value.changes,
+        }
+    }
+}
+
+/// A page of the version history of a BOM, newest version first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionHistory {
+    pub bom_id: Uuid,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+    pub versions: Vec<VersionSummary>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{Component, Price};
+
+    use super::*;
+
+    #[test]
+    fn test_version_summary_counts_lines() {
+        let component = Component {
+            id: Uuid::new_v4(),
+            name: "Component".to_string(),
+            part_number: "12345".to_string(),
+            description: None,
+            supplier: "Supplier".to_string(),
+            price: Price {
+                value: 10.0,
+                currency: "USD".to_string(),
+            },
+        };
+
+        let version = BomVersion::new(
+            &Uuid::new_v4(),
+            2,
+            Box::new(vec![
+                BOMChangeEvent::NameChanged("New name".to_string()),
+                BOMChangeEvent::ComponentAdded(component.clone(), 2),
+                BOMChangeEvent::ComponentUpdated(component.id, 3),
+                BOMChangeEvent::ComponentRemoved(component.clone()),
+                BOMChangeEvent::ComponentReplaced {
+                    old: component.clone(),
+                    new: component.clone(),
+                    qty: 1,
+                },
+            ]),
+            VersionMetadata {
+                author: Some("jane.doe".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let summary = VersionSummary::from(version);
+
+        assert_eq!(summary.version, 2);
+        assert_eq!(summary.metadata.author, Some("jane.doe".to_string()));
+        assert_eq!(
+            summary.summary,
+            vec![
+                "NameChanged(New name)".to_string(),
+                "ComponentAdded(Component, 2)".to_string(),
+                format!("ComponentUpdated({}, 3)", component.id),
+                "ComponentRemoved(Component)".to_string(),
+                "ComponentReplaced(Component, Component, 1)".to_string(),
+            ]
+        );
+        assert_eq!(summary.lines_added, 1);
+        assert_eq!(summary.lines_removed, 1);
+        assert_eq!(summary.lines_updated, 1);
+        assert_eq!(summary.lines_replaced, 1);
+    }
 }
@@ -1,6 +1,11 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::domain::error::DomainError;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Price {
     pub value: f32,
@@ -31,3 +36,47 @@ impl CountedComponent {
         }
     }
 }
+
+/// Where a part is in its manufacturer's lifecycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleStatus {
+    #[default]
+    Active,
+    /// Not recommended for new designs.
+    Nrnd,
+    Obsolete,
+}
+
+impl LifecycleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifecycleStatus::Active => "active",
+            LifecycleStatus::Nrnd => "nrnd",
+            LifecycleStatus::Obsolete => "obsolete",
+        }
+    }
+}
+
+impl FromStr for LifecycleStatus {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(LifecycleStatus::Active),
+            "nrnd" => Ok(LifecycleStatus::Nrnd),
+            "obsolete" => Ok(LifecycleStatus::Obsolete),
+            other => Err(DomainError::ConversionError(format!(
+                "Unknown lifecycle status {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentLifecycle {
+    pub component_id: Uuid,
+    pub status: LifecycleStatus,
+    pub updated_at: DateTime<Utc>,
+}
@@ -22,6 +22,10 @@ pub struct BOMDiff {
     pub components_added: HashMap<Uuid, CountedComponent>,
     pub components_removed: Vec<Component>,
     pub components_updated: HashMap<Uuid, PartialDiff<CountedComponent>>,
+    /// Lines whose component was swapped for another one, keyed by the id of
+    /// the replaced component.
+    #[serde(default)]
+    pub components_replaced: HashMap<Uuid, PartialDiff<CountedComponent>>,
 }
 
 impl From<(&BOM, &Vec<BOMChangeEvent>)> for BOMDiff {
@@ -38,6 +42,280 @@ impl From<(&BOM, &Vec<BOMChangeEvent>)> for BOMDiff {
     }
 }
 
+/// How lines of two BOMs are paired up when computing a diff.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentMatching {
+    /// Lines are the same only if they reference the same component record.
+    #[default]
+    Id,
+    /// Lines that are not paired by component id are additionally paired when
+    /// their components share a part number.
+    PartNumber,
+}
+
+impl BOMDiff {
+    /// Computes the difference between two states of a BOM, matching lines by
+    /// component id.
+    pub fn between(from: &BOM, to: &BOM) -> Self {
+        Self::between_matching(from, to, ComponentMatching::Id)
+    }
+
+    /// Computes the difference between two BOMs. Updated lines are keyed by the
+    /// component id on the `from` side.
+    pub fn between_matching(from: &BOM, to: &BOM, matching: ComponentMatching) -> Self {
+        let mut diff = BOMDiff::default();
+
+        if from.name != to.name {
+            diff.name_changed = Some(PartialDiff {
+                from: from.name.clone(),
+                to: to.name.clone(),
+            });
+        }
+
+        if from.description != to.description {
+            diff.description_changed = Some(PartialDiff {
+                from: from.description.clone().unwrap_or_default(),
+                to: to.description.clone().unwrap_or_default(),
+            });
+        }
+
+        // For every line of `from`, the index of the line of `to` it is paired with
+        let mut pairs: Vec<Option<usize>> = from
+            .components
+            .iter()
+            .map(|cc| {
+                to.components
+                    .iter()
+                    .position(|target| target.component.id == cc.component.id)
+            })
+            .collect();
+
+        if let ComponentMatching::PartNumber = matching {
+            for (i, counted_component) in from.components.iter().enumerate() {
+                if pairs[i].is_some() {
+                    continue;
+                }
+                pairs[i] = to.components.iter().enumerate().position(|(j, target)| {
+                    target.component.part_number == counted_component.component.part_number
+                        && !pairs.contains(&Some(j))
+                });
+            }
+        }
+
+        for (counted_component, pair) in from.components.iter().zip(pairs.iter()) {
+            match pair.map(|j| &to.components[j]) {
+                Some(target) if target != counted_component => {
+                    diff.components_updated.insert(
+                        counted_component.component.id,
+                        PartialDiff {
+                            from: counted_component.clone(),
+                            to: target.clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
+                None => diff
+                    .components_removed
+                    .push(counted_component.component.clone()),
+            }
+        }
+
+        for (j, counted_component) in to.components.iter().enumerate() {
+            if !pairs.contains(&Some(j)) {
+                diff.components_added
+                    .insert(counted_component.component.id, counted_component.clone());
+            }
+        }
+
+        diff
+    }
+}
+
+/// An entry of a diff whose `from` side does not match the BOM the diff is
+/// applied to.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct DiffConflict {
+    /// The section of the diff, and the component for line entries.
+    pub entry: String,
+    pub message: String,
+}
+
+impl DiffConflict {
+    fn new(entry: String, message: String) -> Self {
+        Self { entry, message }
+    }
+}
+
+impl BOMDiff {
+    /// Lists the entries of this diff that expect `bom` to be in another
+    /// state than it is.
+    pub fn conflicts_with(&self, bom: &BOM) -> Vec<DiffConflict> {
+        let mut conflicts = vec![];
+        let line = |id: &Uuid| bom.components.iter().find(|cc| cc.component.id == *id);
+
+        if let Some(name) = &self.name_changed {
+            if name.from != bom.name {
+                conflicts.push(DiffConflict::new(
+                    "name_changed".to_string(),
+                    format!("Expected name {:?} but found {:?}", name.from, bom.name),
+                ));
+            }
+        }
+
+        if let Some(description) = &self.description_changed {
+            let current = bom.description.clone().unwrap_or_default();
+            if description.from != current {
+                conflicts.push(DiffConflict::new(
+                    "description_changed".to_string(),
+                    format!(
+                        "Expected description {:?} but found {:?}",
+                        description.from, current
+                    ),
+                ));
+            }
+        }
+
+        for component in self.components_removed.iter() {
+            if line(&component.id).is_none() {
+                conflicts.push(DiffConflict::new(
+                    format!("components_removed.{}", component.id),
+                    format!("{} is not part of the BOM", component.part_number),
+                ));
+            }
+        }
+
+        for (id, partial_diff) in self.components_updated.iter() {
+            let from = &partial_diff.from;
+            match line(id) {
+                None => conflicts.push(DiffConflict::new(
+                    format!("components_updated.{}", id),
+                    format!("{} is not part of the BOM", from.component.part_number),
+                )),
+                Some(cc) if cc.quantity != from.quantity => conflicts.push(DiffConflict::new(
+                    format!("components_updated.{}", id),
+                    format!(
+                        "Expected {} units of {} but found {}",
+                        from.quantity, from.component.part_number, cc.quantity
+                    ),
+                )),
+                Some(_) => {}
+            }
+        }
+
+        for (id, partial_diff) in self.components_replaced.iter() {
+            let PartialDiff { from, to } = partial_diff;
+            let entry = format!("components_replaced.{}", id);
+            match line(id) {
+                None => conflicts.push(DiffConflict::new(
+                    entry,
+                    format!("{} is not part of the BOM", from.component.part_number),
+                )),
+                Some(cc) if cc.quantity != from.quantity => conflicts.push(DiffConflict::new(
+                    entry,
+                    format!(
+                        "Expected {} units of {} but found {}",
+                        from.quantity, from.component.part_number, cc.quantity
+                    ),
+                )),
+                Some(_) if line(&to.component.id).is_some() => conflicts.push(DiffConflict::new(
+                    entry,
+                    format!("{} is already part of the BOM", to.component.part_number),
+                )),
+                Some(_) => {}
+            }
+        }
+
+        for (id, counted_component) in self.components_added.iter() {
+            if line(id).is_some() {
+                conflicts.push(DiffConflict::new(
+                    format!("components_added.{}", id),
+                    format!(
+                        "{} is already part of the BOM",
+                        counted_component.component.part_number
+                    ),
+                ));
+            }
+        }
+
+        conflicts.sort_by(|a, b| a.entry.cmp(&b.entry));
+        conflicts
+    }
+}
+
+impl From<&BOMDiff> for Vec<BOMChangeEvent> {
+    fn from(diff: &BOMDiff) -> Self {
+        let mut events = vec![];
+
+        if let Some(name) = &diff.name_changed {
+            events.push(BOMChangeEvent::NameChanged(name.to.clone()));
+        }
+
+        // A description cannot be empty, so an empty target means it was removed
+        if let Some(description) = &diff.description_changed {
+            if description.to.is_empty() {
+                events.push(BOMChangeEvent::DescriptionCleared);
+            } else {
+                events.push(BOMChangeEvent::DescriptionChanged(description.to.clone()));
+            }
+        }
+
+        for component in diff.components_removed.iter() {
+            events.push(BOMChangeEvent::ComponentRemoved(component.clone()));
+        }
+
+        let mut updated: Vec<&PartialDiff<CountedComponent>> =
+            diff.components_updated.values().collect();
+        updated.sort_by_key(|partial_diff| partial_diff.from.component.id);
+
+        for partial_diff in updated {
+            let PartialDiff { from, to } = partial_diff;
+            if from.component == to.component {
+                events.push(BOMChangeEvent::ComponentUpdated(
+                    to.component.id,
+                    to.quantity,
+                ));
+            } else if from.component.id != to.component.id {
+                events.push(BOMChangeEvent::ComponentReplaced {
+                    old: from.component.clone(),
+                    new: to.component.clone(),
+                    qty: to.quantity,
+                });
+            } else {
+                events.push(BOMChangeEvent::ComponentRemoved(from.component.clone()));
+                events.push(BOMChangeEvent::ComponentAdded(
+                    to.component.clone(),
+                    to.quantity,
+                ));
+            }
+        }
+
+        let mut replaced: Vec<&PartialDiff<CountedComponent>> =
+            diff.components_replaced.values().collect();
+        replaced.sort_by_key(|partial_diff| partial_diff.from.component.id);
+
+        for PartialDiff { from, to } in replaced {
+            events.push(BOMChangeEvent::ComponentReplaced {
+                old: from.component.clone(),
+                new: to.component.clone(),
+                qty: to.quantity,
+            });
+        }
+
+        let mut added: Vec<&CountedComponent> = diff.components_added.values().collect();
+        added.sort_by_key(|counted_component| counted_component.component.id);
+
+        for counted_component in added {
+            events.push(BOMChangeEvent::ComponentAdded(
+                counted_component.component.clone(),
+                counted_component.quantity,
+            ));
+        }
+
+        events
+    }
+}
+
 pub struct BOMDiffVisitor;
 
 impl BOMChangeEventVisitor for BOMDiffVisitor {
@@ -55,6 +333,13 @@ impl BOMChangeEventVisitor for BOMDiffVisitor {
         });
     }
 
+    fn visit_description_cleared(&mut self, bom: &BOM, diff: &mut BOMDiff) {
+        diff.description_changed = Some(PartialDiff {
+            from: bom.description.clone().unwrap_or_default(),
+            to: String::new(),
+        });
+    }
+
     fn visit_component_added(
         &mut self,
         component: &Component,
@@ -99,9 +384,22 @@ impl BOMChangeEventVisitor for BOMDiffVisitor {
         if let Some(counted_component) = diff.components_added.get_mut(id) {
             counted_component.quantity = qty;
         }
+
+        if let Some(partial_diff) = replacement_of(diff, id)
+            .and_then(|replaced_id| diff.components_replaced.get_mut(&replaced_id))
+        {
+            partial_diff.to.quantity = qty;
+        }
     }
 
     fn visit_component_removed(&mut self, component: &Component, diff: &mut BOMDiff) {
+        if let Some(partial_diff) = replacement_of(diff, &component.id)
+            .and_then(|replaced_id| diff.components_replaced.remove(&replaced_id))
+        {
+            diff.components_removed.push(partial_diff.from.component);
+            return;
+        }
+
         let removed_from_added = diff.components_added.remove(&component.id);
 
         diff.components_updated.remove(&component.id);
@@ -110,13 +408,63 @@ impl BOMChangeEventVisitor for BOMDiffVisitor {
             diff.components_removed.push(component.clone());
         }
     }
+
+    fn visit_component_replaced(
+        &mut self,
+        old: &Component,
+        new: &Component,
+        qty: i32,
+        bom: &BOM,
+        diff: &mut BOMDiff,
+    ) {
+        let to = CountedComponent::new(new.clone(), qty);
+
+        if diff.components_added.remove(&old.id).is_some() {
+            diff.components_added.insert(new.id, to);
+            return;
+        }
+
+        // A line replaced twice still records the component it started with
+        let from = match replacement_of(diff, &old.id) {
+            Some(replaced_id) => diff
+                .components_replaced
+                .remove(&replaced_id)
+                .map(|partial_diff| partial_diff.from),
+            None => bom
+                .components
+                .iter()
+                .find(|cc| cc.component.id == old.id)
+                .cloned(),
+        };
+        let Some(from) = from else {
+            return;
+        };
+
+        diff.components_updated.remove(&old.id);
+
+        if from.component.id != new.id {
+            diff.components_replaced
+                .insert(from.component.id, PartialDiff { from, to });
+        } else if from != to {
+            diff.components_updated
+                .insert(new.id, PartialDiff { from, to });
+        }
+    }
+}
+
+/// The id of the component that the line now holding `id` replaced.
+fn replacement_of(diff: &BOMDiff, id: &Uuid) -> Option<Uuid> {
+    diff.components_replaced
+        .iter()
+        .find(|(_, partial_diff)| partial_diff.to.component.id == *id)
+        .map(|(replaced_id, _)| *replaced_id)
 }
 
 #[cfg(test)]
 mod tests {
     use chrono::Utc;
 
-    use crate::domain::Price;
+    use crate::domain::{validation::BOMChangeEventValidator, Price};
 
     use super::*;
 
@@ -152,6 +500,9 @@ mod tests {
             components: vec![CountedComponent::new(component_1.clone(), 1)],
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            archived_at: None,
+            category: None,
+            rule_set: None,
         };
 
         (bom, component_1, component_2)
@@ -355,6 +706,130 @@ mod tests {
         assert_eq!(diff.components_removed, vec![component_1]);
     }
 
+    #[test]
+    fn test_component_replaced() {
+        let (bom, component_1, component_2) = setup_test_bom_and_components();
+
+        let diff = BOMDiff::from((
+            &bom,
+            &vec![
+                BOMChangeEvent::ComponentUpdated(component_1.id, 2),
+                BOMChangeEvent::ComponentReplaced {
+                    old: component_1.clone(),
+                    new: component_2.clone(),
+                    qty: 3,
+                },
+            ],
+        ));
+
+        assert!(diff.components_added.is_empty());
+        assert!(diff.components_removed.is_empty());
+        assert!(diff.components_updated.is_empty());
+        assert_eq!(
+            diff.components_replaced.get(&component_1.id),
+            Some(&PartialDiff {
+                from: CountedComponent::new(component_1, 1),
+                to: CountedComponent::new(component_2, 3),
+            })
+        );
+    }
+
+    #[test]
+    fn test_component_replaced_then_updated_and_removed() {
+        let (bom, component_1, component_2) = setup_test_bom_and_components();
+        let replaced = BOMChangeEvent::ComponentReplaced {
+            old: component_1.clone(),
+            new: component_2.clone(),
+            qty: 3,
+        };
+
+        let updated = BOMDiff::from((
+            &bom,
+            &vec![
+                replaced.clone(),
+                BOMChangeEvent::ComponentUpdated(component_2.id, 5),
+            ],
+        ));
+        let removed = BOMDiff::from((
+            &bom,
+            &vec![
+                replaced,
+                BOMChangeEvent::ComponentRemoved(component_2.clone()),
+            ],
+        ));
+
+        assert_eq!(updated.components_replaced[&component_1.id].to.quantity, 5);
+        assert!(removed.components_replaced.is_empty());
+        assert_eq!(removed.components_removed, vec![component_1]);
+    }
+
+    #[test]
+    fn test_component_added_then_replaced() {
+        let (bom, _, component_2) = setup_test_bom_and_components();
+        let component_3 = Component {
+            id: Uuid::new_v4(),
+            ..component_2.clone()
+        };
+
+        let diff = BOMDiff::from((
+            &bom,
+            &vec![
+                BOMChangeEvent::ComponentAdded(component_2.clone(), 1),
+                BOMChangeEvent::ComponentReplaced {
+                    old: component_2.clone(),
+                    new: component_3.clone(),
+                    qty: 2,
+                },
+            ],
+        ));
+
+        assert!(diff.components_replaced.is_empty());
+        assert!(!diff.components_added.contains_key(&component_2.id));
+        assert_eq!(
+            diff.components_added.get(&component_3.id),
+            Some(&CountedComponent::new(component_3, 2))
+        );
+    }
+
+    #[test]
+    fn test_events_from_diff_replace_lines_in_place() {
+        let (mut bom, component_1, component_2) = setup_test_bom_and_components();
+        let last = Component {
+            id: Uuid::new_v4(),
+            ..component_2.clone()
+        };
+        bom.components.push(CountedComponent::new(last.clone(), 1));
+
+        let events = vec![BOMChangeEvent::ComponentReplaced {
+            old: component_1,
+            new: component_2.clone(),
+            qty: 4,
+        }];
+        let diff = BOMDiff::from((&bom, &events));
+
+        assert!(diff.conflicts_with(&bom).is_empty());
+        assert_eq!(Vec::<BOMChangeEvent>::from(&diff), events);
+
+        for event in events.iter() {
+            bom.apply_change(event, BOMChangeEventValidator).unwrap();
+        }
+
+        assert_eq!(
+            bom.components,
+            vec![
+                CountedComponent::new(component_2, 4),
+                CountedComponent::new(last, 1),
+            ]
+        );
+        assert_eq!(
+            diff.conflicts_with(&bom)
+                .iter()
+                .map(|conflict| conflict.message.clone())
+                .collect::<Vec<_>>(),
+            vec!["12345 is not part of the BOM".to_string()]
+        );
+    }
+
     #[test]
     fn test_component_removed_readded() {
         let (bom, component_1, _) = setup_test_bom_and_components();
@@ -460,6 +935,239 @@ mod tests {
         assert_eq!(diff.components_removed, vec![component_1]);
     }
 
+    #[test]
+    fn test_between_identical_boms() {
+        let (bom, _, _) = setup_test_bom_and_components();
+
+        let diff = BOMDiff::between(&bom, &bom.clone());
+
+        assert_eq!(diff, BOMDiff::default());
+    }
+
+    #[test]
+    fn test_between_detects_all_changes() {
+        let (bom, component_1, component_2) = setup_test_bom_and_components();
+
+        let mut target = bom.clone();
+        target.name = "New Name".to_string();
+        target.description = Some("New description".to_string());
+        target.components = vec![CountedComponent::new(component_2.clone(), 4)];
+
+        let diff = BOMDiff::between(&bom, &target);
+
+        assert_eq!(
+            diff.name_changed,
+            Some(PartialDiff {
+                from: "Test BOM".to_string(),
+                to: "New Name".to_string()
+            })
+        );
+        assert_eq!(
+            diff.description_changed,
+            Some(PartialDiff {
+                from: "Test description".to_string(),
+                to: "New description".to_string()
+            })
+        );
+        assert_eq!(
+            diff.components_added.get(&component_2.id),
+            Some(&CountedComponent::new(component_2, 4))
+        );
+        assert_eq!(diff.components_removed, vec![component_1]);
+        assert!(diff.components_updated.is_empty());
+    }
+
+    #[test]
+    fn test_between_detects_quantity_change() {
+        let (bom, component_1, _) = setup_test_bom_and_components();
+
+        let mut target = bom.clone();
+        target.components[0].quantity = 3;
+
+        let diff = BOMDiff::between(&bom, &target);
+
+        assert_eq!(
+            diff.components_updated.get(&component_1.id),
+            Some(&PartialDiff {
+                from: CountedComponent::new(component_1.clone(), 1),
+                to: CountedComponent::new(component_1, 3)
+            })
+        );
+    }
+
+    #[test]
+    fn test_between_matching_by_part_number() {
+        let (bom, component_1, _) = setup_test_bom_and_components();
+
+        let same_part = Component {
+            id: Uuid::new_v4(),
+            supplier: "Other Supplier".to_string(),
+            ..component_1.clone()
+        };
+
+        let mut other_bom = bom.clone();
+        other_bom.id = Uuid::new_v4();
+        other_bom.components = vec![CountedComponent::new(same_part.clone(), 2)];
+
+        let by_id = BOMDiff::between_matching(&bom, &other_bom, ComponentMatching::Id);
+        let by_part_number =
+            BOMDiff::between_matching(&bom, &other_bom, ComponentMatching::PartNumber);
+
+        assert_eq!(by_id.components_removed, vec![component_1.clone()]);
+        assert!(by_id.components_added.contains_key(&same_part.id));
+
+        assert!(by_part_number.components_removed.is_empty());
+        assert!(by_part_number.components_added.is_empty());
+        assert_eq!(
+            by_part_number.components_updated.get(&component_1.id),
+            Some(&PartialDiff {
+                from: CountedComponent::new(component_1, 1),
+                to: CountedComponent::new(same_part, 2)
+            })
+        );
+    }
+
+    #[test]
+    fn test_between_matching_by_part_number_prefers_same_record() {
+        let (mut bom, component_1, _) = setup_test_bom_and_components();
+
+        let same_part = Component {
+            id: Uuid::new_v4(),
+            ..component_1.clone()
+        };
+        bom.components
+            .push(CountedComponent::new(same_part.clone(), 1));
+
+        let mut other_bom = bom.clone();
+        other_bom.components = vec![CountedComponent::new(component_1.clone(), 1)];
+
+        let diff = BOMDiff::between_matching(&bom, &other_bom, ComponentMatching::PartNumber);
+
+        assert_eq!(diff.components_removed, vec![same_part]);
+        assert!(diff.components_updated.is_empty());
+        assert!(diff.components_added.is_empty());
+    }
+
+    #[test]
+    fn test_events_from_diff_transform_bom_into_target() {
+        let (mut bom, component_1, component_2) = setup_test_bom_and_components();
+
+        let component_3 = Component {
+            id: Uuid::new_v4(),
+            name: "Component 3".to_string(),
+            description: None,
+            part_number: "67890".to_string(),
+            supplier: "Test Supplier".to_string(),
+            price: Price {
+                value: 5.0,
+                currency: "USD".to_string(),
+            },
+        };
+        bom.components
+            .push(CountedComponent::new(component_2.clone(), 2));
+
+        let mut target = bom.clone();
+        target.name = "Target".to_string();
+        target.components = vec![
+            CountedComponent::new(component_2, 5),
+            CountedComponent::new(component_3, 1),
+        ];
+
+        let events = Vec::<BOMChangeEvent>::from(&BOMDiff::between(&bom, &target));
+
+        assert_eq!(events.len(), 4);
+        assert!(events.contains(&BOMChangeEvent::ComponentRemoved(component_1)));
+
+        for event in events.iter() {
+            bom.apply_change(event, BOMChangeEventValidator).unwrap();
+        }
+
+        assert!(bom.has_same_content(&target));
+    }
+
+    #[test]
+    fn test_events_from_diff_clear_removed_description() {
+        let (mut bom, _, _) = setup_test_bom_and_components();
+
+        let mut target = bom.clone();
+        target.description = None;
+
+        let events = Vec::<BOMChangeEvent>::from(&BOMDiff::between(&bom, &target));
+
+        assert_eq!(events, vec![BOMChangeEvent::DescriptionCleared]);
+
+        for event in events.iter() {
+            bom.apply_change(event, BOMChangeEventValidator).unwrap();
+        }
+
+        assert!(bom.has_same_content(&target));
+    }
+
+    #[test]
+    fn test_description_cleared() {
+        let (bom, _, _) = setup_test_bom_and_components();
+
+        let diff = BOMDiff::from((&bom, &vec![BOMChangeEvent::DescriptionCleared]));
+
+        assert_eq!(
+            diff.description_changed,
+            Some(PartialDiff {
+                from: "Test description".to_string(),
+                to: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_diff_between_boms_applies_to_its_source_without_conflicts() {
+        let (bom, component_1, component_2) = setup_test_bom_and_components();
+
+        let mut target = bom.clone();
+        target.name = "Target".to_string();
+        target.components = vec![
+            CountedComponent::new(component_1, 7),
+            CountedComponent::new(component_2, 1),
+        ];
+
+        assert!(BOMDiff::between(&bom, &target)
+            .conflicts_with(&bom)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_diff_conflicts_with_diverged_bom() {
+        let (bom, component_1, component_2) = setup_test_bom_and_components();
+
+        let mut target = bom.clone();
+        target.name = "Target".to_string();
+        target.components = vec![
+            CountedComponent::new(component_1.clone(), 7),
+            CountedComponent::new(component_2.clone(), 1),
+        ];
+        let diff = BOMDiff::between(&bom, &target);
+
+        let mut diverged = bom.clone();
+        diverged.name = "Diverged".to_string();
+        diverged.components = vec![
+            CountedComponent::new(component_1.clone(), 3),
+            CountedComponent::new(component_2.clone(), 1),
+        ];
+
+        let conflicts = diff.conflicts_with(&diverged);
+
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| conflict.entry.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                format!("components_added.{}", component_2.id),
+                format!("components_updated.{}", component_1.id),
+                "name_changed".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn test_no_events() {
         let (bom, _, _) = setup_test_bom_and_components();
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::VersionedBOM;
+
+/// A request stored under its `Idempotency-Key`. The response is missing
+/// while the first request is still being processed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotencyRecord {
+    pub key: String,
+    pub request_hash: String,
+    pub response: Option<VersionedBOM>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The outcome of a write guarded by an idempotency key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotentWrite {
+    pub bom: VersionedBOM,
+    /// Whether `bom` is the stored response of an earlier request.
+    pub replayed: bool,
+}
@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Records that `bom_id` was cloned from `source_version` of `source_bom_id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineageLink {
+    pub bom_id: Uuid,
+    pub source_bom_id: Uuid,
+    pub source_version: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LineageLink {
+    pub fn new(bom_id: Uuid, source_bom_id: Uuid, source_version: i32) -> Self {
+        Self {
+            bom_id,
+            source_bom_id,
+            source_version,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// The BOMs a BOM was derived from, closest first, and every BOM derived from
+/// it, directly or not.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BOMLineage {
+    pub bom_id: Uuid,
+    pub ancestors: Vec<LineageLink>,
+    pub descendants: Vec<LineageLink>,
+}
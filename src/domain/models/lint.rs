@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{CountedComponent, LifecycleStatus, BOM};
+
+/// Lines with at least this many units are reported as suspicious.
+const SUSPICIOUS_QUANTITY: i32 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintCheck {
+    MissingPrice,
+    SingleSupplier,
+    MixedCurrencies,
+    ObsoletePart,
+    NrndPart,
+    DuplicatePartNumber,
+    SuspiciousQuantity,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintFinding {
+    pub check: LintCheck,
+    pub severity: Severity,
+    pub message: String,
+    /// The line the finding is about, if it is about a single line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component_id: Option<Uuid>,
+}
+
+impl LintFinding {
+    fn new(check: LintCheck, severity: Severity, message: String) -> Self {
+        Self {
+            check,
+            severity,
+            message,
+            component_id: None,
+        }
+    }
+
+    fn on_line(mut self, component_id: Uuid) -> Self {
+        self.component_id = Some(component_id);
+        self
+    }
+}
+
+/// Non-blocking findings about a version of a BOM, most severe first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BOMLint {
+    pub bom_id: Uuid,
+    pub version: i32,
+    pub findings: Vec<LintFinding>,
+}
+
+impl BOMLint {
+    /// Checks `bom`. Components missing from `lifecycles` count as active.
+    pub fn run(bom: &BOM, lifecycles: &HashMap<Uuid, LifecycleStatus>) -> Self {
+        let mut findings = vec![];
+
+        for line in bom.components.iter() {
+            let component = &line.component;
+
+            let price = &component.price;
+            if price.value.is_nan() || price.value <= 0.0 || price.currency.trim().is_empty() {
+                findings.push(
+                    LintFinding::new(
+                        LintCheck::MissingPrice,
+                        Severity::Error,
+                        format!("{} has no price", component.part_number),
+                    )
+                    .on_line(component.id),
+                );
+            }
+
+            match lifecycles.get(&component.id) {
+                Some(LifecycleStatus::Obsolete) => findings.push(
+                    LintFinding::new(
+                        LintCheck::ObsoletePart,
+                        Severity::Error,
+                        format!("{} is obsolete", component.part_number),
+                    )
+                    .on_line(component.id),
+                ),
+                Some(LifecycleStatus::Nrnd) => findings.push(
+                    LintFinding::new(
+                        LintCheck::NrndPart,
+                        Severity::Warning,
+                        format!(
+                            "{} is not recommended for new designs",
+                            component.part_number
+                        ),
+                    )
+                    .on_line(component.id),
+                ),
+                _ => {}
+            }
+
+            if line.quantity >= SUSPICIOUS_QUANTITY {
+                findings.push(
+                    LintFinding::new(
+                        LintCheck::SuspiciousQuantity,
+                        Severity::Warning,
+                        format!(
+                            "{} units of {} look suspicious",
+                            line.quantity, component.part_number
+                        ),
+                    )
+                    .on_line(component.id),
+                );
+            }
+        }
+
+        let mut part_numbers: HashMap<&str, usize> = HashMap::new();
+        for line in bom.components.iter() {
+            *part_numbers
+                .entry(line.component.part_number.as_str())
+                .or_default() += 1;
+        }
+        let mut duplicates: Vec<(&str, usize)> = part_numbers
+            .into_iter()
+            .filter(|(_, lines)| *lines > 1)
+            .collect();
+        duplicates.sort_unstable();
+        findings.extend(duplicates.into_iter().map(|(part_number, lines)| {
+            LintFinding::new(
+                LintCheck::DuplicatePartNumber,
+                Severity::Warning,
+                format!("Part number {} is used by {} lines", part_number, lines),
+            )
+        }));
+
+        let mut suppliers: Vec<&str> = distinct(bom, |line| line.component.supplier.as_str());
+        if bom.components.len() > 1 && suppliers.len() == 1 {
+            findings.push(LintFinding::new(
+                LintCheck::SingleSupplier,
+                Severity::Info,
+                format!("Every component comes from {}", suppliers.remove(0)),
+            ));
+        }
+
+        let currencies = distinct(bom, |line| line.component.price.currency.as_str());
+        if currencies.len() > 1 {
+            findings.push(LintFinding::new(
+                LintCheck::MixedCurrencies,
+                Severity::Warning,
+                format!(
+                    "Prices are in several currencies: {}",
+                    currencies.join(", ")
+                ),
+            ));
+        }
+
+        findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+
+        Self {
+            bom_id: bom.id,
+            version: bom.version,
+            findings,
+        }
+    }
+}
+
+fn distinct<'a>(bom: &'a BOM, key: impl Fn(&'a CountedComponent) -> &'a str) -> Vec<&'a str> {
+    let mut values: Vec<&str> = bom.components.iter().map(key).collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{Component, Price};
+
+    use super::*;
+
+    fn create_test_component(part_number: &str, supplier: &str, price: Price) -> Component {
+        Component {
+            id: Uuid::new_v4(),
+            name: "Component".to_string(),
+            part_number: part_number.to_string(),
+            description: None,
+            supplier: supplier.to_string(),
+            price,
+        }
+    }
+
+    fn price(value: f32, currency: &str) -> Price {
+        Price {
+            value,
+            currency: currency.to_string(),
+        }
+    }
+
+    fn checks(lint: &BOMLint) -> Vec<LintCheck> {
+        lint.findings.iter().map(|finding| finding.check).collect()
+    }
+
+    #[test]
+    fn test_lint_reports_findings_most_severe_first() {
+        let free = create_test_component("PRT-1", "Supplier A", price(0.0, "USD"));
+        let obsolete = create_test_component("PRT-2", "Supplier B", price(1.0, "EUR"));
+        let nrnd = create_test_component("PRT-2", "Supplier B", price(1.0, "EUR"));
+        let mut bom = BOM::default();
+        bom.components.extend([
+            CountedComponent::new(free.clone(), 1),
+            CountedComponent::new(obsolete.clone(), 1),
+            CountedComponent::new(nrnd.clone(), SUSPICIOUS_QUANTITY),
+        ]);
+        let lifecycles = HashMap::from([
+            (obsolete.id, LifecycleStatus::Obsolete),
+            (nrnd.id, LifecycleStatus::Nrnd),
+        ]);
+
+        let lint = BOMLint::run(&bom, &lifecycles);
+
+        assert_eq!(
+            checks(&lint),
+            vec![
+                LintCheck::MissingPrice,
+                LintCheck::ObsoletePart,
+                LintCheck::NrndPart,
+                LintCheck::SuspiciousQuantity,
+                LintCheck::DuplicatePartNumber,
+                LintCheck::MixedCurrencies,
+            ]
+        );
+        assert_eq!(lint.findings[0].component_id, Some(free.id));
+    }
+
+    #[test]
+    fn test_lint_reports_single_supplier() {
+        let mut bom = BOM::default();
+        bom.components.extend([
+            CountedComponent::new(
+                create_test_component("PRT-1", "Supplier", price(1.0, "USD")),
+                1,
+            ),
+            CountedComponent::new(
+                create_test_component("PRT-2", "Supplier", price(2.0, "USD")),
+                1,
+            ),
+        ]);
+
+        let lint = BOMLint::run(&bom, &HashMap::new());
+
+        assert_eq!(checks(&lint), vec![LintCheck::SingleSupplier]);
+        assert_eq!(lint.findings[0].severity, Severity::Info);
+    }
+
+    #[test]
+    fn test_lint_of_clean_bom_has_no_findings() {
+        let mut bom = BOM::default();
+        bom.components.extend([
+            CountedComponent::new(
+                create_test_component("PRT-1", "Supplier A", price(1.0, "USD")),
+                2,
+            ),
+            CountedComponent::new(
+                create_test_component("PRT-2", "Supplier B", price(2.0, "USD")),
+                4,
+            ),
+        ]);
+
+        let lint = BOMLint::run(&bom, &HashMap::new());
+
+        assert!(lint.findings.is_empty());
+    }
+}
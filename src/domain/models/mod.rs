@@ -1,11 +1,23 @@
 pub mod bom;
+pub mod bom_blame;
 pub mod bom_change_event;
+pub mod bom_snapshot;
 pub mod bom_version;
 pub mod component;
 pub mod diff;
+pub mod idempotency;
+pub mod lineage;
+pub mod lint;
+pub mod preview;
 
 pub use bom::*;
+pub use bom_blame::*;
 pub use bom_change_event::*;
+pub use bom_snapshot::*;
 pub use bom_version::*;
 pub use component::*;
 pub use diff::*;
+pub use idempotency::*;
+pub use lineage::*;
+pub use lint::*;
+pub use preview::*;
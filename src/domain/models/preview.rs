@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{BOMChangeEvent, BOMDiff, BOM};
+
+/// The outcome of an update computed on a copy of a BOM, without writing it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BOMPreview {
+    pub bom: BOM,
+    pub diff: BOMDiff,
+    /// How the total cost changes, per currency.
+    pub cost_delta: BTreeMap<String, f32>,
+}
+
+impl BOMPreview {
+    /// Describes `bom`, the result of applying `events` to `current`.
+    pub fn new(current: &BOM, events: &Vec<BOMChangeEvent>, bom: BOM) -> Self {
+        let mut cost_delta = bom.total_cost();
+        for (currency, total) in current.total_cost() {
+            *cost_delta.entry(currency).or_default() -= total;
+        }
+
+        Self {
+            diff: BOMDiff::from((current, events)),
+            bom,
+            cost_delta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::domain::{Component, CountedComponent, Price};
+
+    use super::*;
+
+    fn create_test_component(value: f32, currency: &str) -> Component {
+        Component {
+            id: Uuid::new_v4(),
+            name: "Component".to_string(),
+            part_number: "PRT-1".to_string(),
+            description: None,
+            supplier: "Supplier".to_string(),
+            price: Price {
+                value,
+                currency: currency.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_preview_reports_cost_delta_per_currency() {
+        let dollars = create_test_component(2.5, "USD");
+        let euros = create_test_component(4.0, "EUR");
+        let current = BOM {
+            components: vec![
+                CountedComponent::new(dollars.clone(), 2),
+                CountedComponent::new(euros.clone(), 1),
+            ],
+            ..Default::default()
+        };
+        let events = vec![
+            BOMChangeEvent::ComponentUpdated(dollars.id, 6),
+            BOMChangeEvent::ComponentRemoved(euros.clone()),
+        ];
+        let mut bom = current.clone();
+        bom.components = vec![CountedComponent::new(dollars.clone(), 6)];
+
+        let preview = BOMPreview::new(&current, &events, bom);
+
+        assert_eq!(
+            preview.cost_delta,
+            BTreeMap::from([("EUR".to_string(), -4.0), ("USD".to_string(), 10.0)])
+        );
+        assert_eq!(preview.diff.components_removed, vec![euros]);
+        assert_eq!(preview.diff.components_updated.len(), 1);
+    }
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{BOMDiff, VersionMetadata};
+
+/// Asks for a diff, e.g. one computed between two other BOMs, to be applied.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApplyDiff {
+    pub diff: BOMDiff,
+    #[serde(flatten)]
+    pub metadata: VersionMetadata,
+}
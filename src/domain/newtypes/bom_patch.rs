@@ -0,0 +1,318 @@
+use json_patch::{Patch, PatchOperation};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::{error::DomainError, BOM};
+
+use super::change_request::{BOMChangeRequest, ComponentReference};
+
+/// Fields of a BOM that no change event can alter.
+const READ_ONLY_FIELDS: [&str; 7] = [
+    "id",
+    "version",
+    "created_at",
+    "updated_at",
+    "archived_at",
+    "category",
+    "rule_set",
+];
+
+/// An RFC 6902 JSON Patch against the representation of a BOM.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct BOMPatch(pub Patch);
+
+/// The parts of a patched BOM that changes are derived from. Lines may
+/// reference their component instead of holding all of it.
+#[derive(Deserialize)]
+struct PatchedBOM {
+    name: String,
+    description: Option<String>,
+    components: Vec<PatchedLine>,
+}
+
+#[derive(Deserialize)]
+struct PatchedLine {
+    component: ComponentReference,
+    quantity: i32,
+}
+
+impl BOMPatch {
+    /// Applies the patch to `bom` and returns the changes that lead from
+    /// `bom` to the patched document.
+    pub fn to_change_requests(&self, bom: &BOM) -> Result<Vec<BOMChangeRequest>, DomainError> {
+        self.ensure_writable_paths()?;
+
+        let mut document =
+            serde_json::to_value(bom).map_err(|e| DomainError::ConversionError(e.to_string()))?;
+        json_patch::patch(&mut document, &self.0)
+            .map_err(|e| DomainError::ValidationError(e.to_string()))?;
+        let patched: PatchedBOM = serde_json::from_value(document)
+            .map_err(|e| DomainError::ValidationError(format!("Patched BOM is invalid: {}", e)))?;
+
+        let mut requests = vec![];
+
+        if patched.name != bom.name {
+            requests.push(BOMChangeRequest::NameChanged(patched.name));
+        }
+
+        match patched.description {
+            Some(description) if bom.description.as_ref() != Some(&description) => {
+                requests.push(BOMChangeRequest::DescriptionChanged(description))
+            }
+            None if bom.description.is_some() => {
+                requests.push(BOMChangeRequest::DescriptionCleared)
+            }
+            _ => {}
+        }
+
+        let kept = |id: &Uuid| {
+            patched
+                .components
+                .iter()
+                .any(|line| line.component == ComponentReference::Id { id: *id })
+        };
+        let current_line = |reference: &ComponentReference| {
+            bom.components.iter().find(
+                |cc| matches!(reference, ComponentReference::Id { id } if *id == cc.component.id),
+            )
+        };
+
+        // Swapping the component of a line replaces it in place
+        let swapped = self.swapped_lines();
+        let replaced: Vec<Option<Uuid>> = patched
+            .components
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                bom.components
+                    .get(i)
+                    .filter(|cc| {
+                        swapped.contains(&i)
+                            && current_line(&line.component).is_none()
+                            && !kept(&cc.component.id)
+                    })
+                    .map(|cc| cc.component.id)
+            })
+            .collect();
+
+        requests.extend(
+            bom.components
+                .iter()
+                .filter(|cc| !kept(&cc.component.id) && !replaced.contains(&Some(cc.component.id)))
+                .map(|cc| {
+                    BOMChangeRequest::ComponentRemoved(ComponentReference::Id {
+                        id: cc.component.id,
+                    })
+                }),
+        );
+
+        for (line, replaced) in patched.components.into_iter().zip(replaced) {
+            match (current_line(&line.component), replaced) {
+                (Some(cc), _) if cc.quantity != line.quantity => requests.push(
+                    BOMChangeRequest::ComponentUpdated(cc.component.id, line.quantity),
+                ),
+                (Some(_), _) => {}
+                (None, Some(id)) => requests.push(BOMChangeRequest::ComponentReplaced {
+                    old: ComponentReference::Id { id },
+                    new: line.component,
+                    qty: line.quantity,
+                }),
+                (None, None) => requests.push(BOMChangeRequest::ComponentAdded(
+                    line.component,
+                    line.quantity,
+                )),
+            }
+        }
+
+        Ok(requests)
+    }
+
+    /// Positions of the lines whose component is replaced by the patch.
+    fn swapped_lines(&self) -> Vec<usize> {
+        let Patch(operations) = &self.0;
+
+        operations
+            .iter()
+            .filter_map(|operation| match operation {
+                PatchOperation::Replace(op) => {
+                    match op.path.split('/').skip(1).collect::<Vec<_>>().as_slice() {
+                        ["components", i, "component"] => i.parse().ok(),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn ensure_writable_paths(&self) -> Result<(), DomainError> {
+        for operation in self.0 .0.iter() {
+            let paths = match operation {
+                PatchOperation::Test(_) => vec![],
+                PatchOperation::Add(op) => vec![&op.path],
+                PatchOperation::Remove(op) => vec![&op.path],
+                PatchOperation::Replace(op) => vec![&op.path],
+                PatchOperation::Move(op) => vec![&op.from, &op.path],
+                PatchOperation::Copy(op) => vec![&op.path],
+            };
+
+            for path in paths {
+                let segments: Vec<&str> = path.split('/').skip(1).collect();
+
+                match segments.as_slice() {
+                    [] => {
+                        return Err(DomainError::ValidationError(
+                            "The whole BOM cannot be replaced".to_string(),
+                        ))
+                    }
+                    [field, ..] if READ_ONLY_FIELDS.contains(field) => {
+                        return Err(DomainError::ValidationError(format!(
+                            "{} is read-only",
+                            path
+                        )))
+                    }
+                    ["components", _, "component", _, ..] => {
+                        return Err(DomainError::ValidationError(format!(
+                            "{} is catalog data. Reference another component instead",
+                            path
+                        )))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::domain::{Component, CountedComponent, Price};
+
+    use super::*;
+
+    fn create_test_component() -> Component {
+        Component {
+            id: Uuid::new_v4(),
+            name: "Component".to_string(),
+            part_number: "PRT-1".to_string(),
+            description: None,
+            supplier: "Supplier".to_string(),
+            price: Price {
+                value: 10.0,
+                currency: "USD".to_string(),
+            },
+        }
+    }
+
+    fn patch(operations: serde_json::Value) -> BOMPatch {
+        serde_json::from_value(operations).unwrap()
+    }
+
+    fn setup_test_bom() -> (BOM, Component, Component) {
+        let kept = create_test_component();
+        let removed = create_test_component();
+        let mut bom = BOM {
+            name: "BOM".to_string(),
+            ..Default::default()
+        };
+        bom.components.extend([
+            CountedComponent::new(kept.clone(), 1),
+            CountedComponent::new(removed.clone(), 1),
+        ]);
+        (bom, kept, removed)
+    }
+
+    #[test]
+    fn test_patch_translates_operations_into_change_requests() {
+        let (bom, kept, removed) = setup_test_bom();
+        let added = Uuid::new_v4();
+
+        let requests = patch(json!([
+            { "op": "replace", "path": "/name", "value": "Renamed" },
+            { "op": "add", "path": "/description", "value": "Described" },
+            { "op": "replace", "path": "/components/0/quantity", "value": 4 },
+            { "op": "remove", "path": "/components/1" },
+            { "op": "add", "path": "/components/-", "value": { "component": { "id": added }, "quantity": 2 } },
+        ]))
+        .to_change_requests(&bom)
+        .unwrap();
+
+        assert_eq!(
+            requests,
+            vec![
+                BOMChangeRequest::NameChanged("Renamed".to_string()),
+                BOMChangeRequest::DescriptionChanged("Described".to_string()),
+                BOMChangeRequest::ComponentRemoved(ComponentReference::Id { id: removed.id }),
+                BOMChangeRequest::ComponentUpdated(kept.id, 4),
+                BOMChangeRequest::ComponentAdded(ComponentReference::Id { id: added }, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patch_replacing_a_component_replaces_the_line() {
+        let (bom, _, removed) = setup_test_bom();
+
+        let requests = patch(json!([
+            { "op": "replace", "path": "/components/1/component", "value": { "part_number": "PRT-9" } },
+        ]))
+        .to_change_requests(&bom)
+        .unwrap();
+
+        assert_eq!(
+            requests,
+            vec![BOMChangeRequest::ComponentReplaced {
+                old: ComponentReference::Id { id: removed.id },
+                new: ComponentReference::PartNumber {
+                    part_number: "PRT-9".to_string()
+                },
+                qty: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_patch_rejects_read_only_and_catalog_paths() {
+        let (bom, _, _) = setup_test_bom();
+
+        for operation in [
+            json!({ "op": "replace", "path": "/id", "value": Uuid::new_v4() }),
+            json!({ "op": "replace", "path": "/version", "value": 9 }),
+            json!({ "op": "move", "from": "/created_at", "path": "/name" }),
+            json!({ "op": "replace", "path": "/components/0/component/price/value", "value": 0 }),
+            json!({ "op": "replace", "path": "", "value": {} }),
+        ] {
+            assert!(patch(json!([operation])).to_change_requests(&bom).is_err());
+        }
+    }
+
+    #[test]
+    fn test_patch_removing_the_description_clears_it() {
+        let (mut bom, _, _) = setup_test_bom();
+        bom.description = Some("Described".to_string());
+
+        let requests = patch(json!([{ "op": "remove", "path": "/description" }]))
+            .to_change_requests(&bom)
+            .unwrap();
+
+        assert_eq!(requests, vec![BOMChangeRequest::DescriptionCleared]);
+    }
+
+    #[test]
+    fn test_patch_with_failing_test_operation_is_rejected() {
+        let (bom, _, _) = setup_test_bom();
+
+        let result = patch(json!([
+            { "op": "test", "path": "/version", "value": 7 },
+            { "op": "replace", "path": "/name", "value": "Renamed" },
+        ]))
+        .to_change_requests(&bom);
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer};
+use uuid::Uuid;
+
+use crate::domain::error::DomainError;
+
+/// Points at a BOM, optionally at one of its versions, written as
+/// `{bom_id}@{version}`. Without a version the current state is meant.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BOMReference {
+    pub bom_id: Uuid,
+    pub version: Option<i32>,
+}
+
+impl Display for BOMReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.version {
+            Some(version) => write!(f, "{}@{}", self.bom_id, version),
+            None => write!(f, "{}", self.bom_id),
+        }
+    }
+}
+
+impl FromStr for BOMReference {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            DomainError::ValidationError(format!(
+                "Invalid BOM reference {}. Expected {{bom_id}}@{{version}}",
+                s
+            ))
+        };
+
+        let (bom_id, version) = match s.split_once('@') {
+            Some((bom_id, version)) => (bom_id, Some(version)),
+            None => (s, None),
+        };
+
+        Ok(Self {
+            bom_id: Uuid::parse_str(bom_id).map_err(|_| invalid())?,
+            version: version
+                .map(|version| version.parse::<i32>().map_err(|_| invalid()))
+                .transpose()?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for BOMReference {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<BOMReference>().map_err(|e| match e {
+            DomainError::ValidationError(message) | DomainError::ConversionError(message) => {
+                serde::de::Error::custom(message)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reference_with_version() {
+        let bom_id = Uuid::new_v4();
+
+        let reference = format!("{}@3", bom_id).parse::<BOMReference>();
+
+        assert_eq!(
+            reference,
+            Ok(BOMReference {
+                bom_id,
+                version: Some(3)
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_reference_without_version() {
+        let bom_id = Uuid::new_v4();
+
+        let reference = bom_id.to_string().parse::<BOMReference>();
+
+        assert_eq!(
+            reference,
+            Ok(BOMReference {
+                bom_id,
+                version: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_reference() {
+        assert!("not-a-uuid@1".parse::<BOMReference>().is_err());
+        assert!(format!("{}@latest", Uuid::new_v4())
+            .parse::<BOMReference>()
+            .is_err());
+    }
+}
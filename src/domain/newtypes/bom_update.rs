@@ -0,0 +1,111 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::VersionMetadata;
+
+use super::change_request::BOMChangeRequest;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BOMUpdateBody")]
+pub struct BOMUpdate {
+    pub events: Vec<BOMChangeRequest>,
+    /// The version the update was based on. When set, the update is rejected
+    /// if the BOM has moved on in the meantime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_version: Option<i32>,
+    #[serde(flatten)]
+    pub metadata: VersionMetadata,
+}
+
+/// The accepted request bodies: the full update, or just its events as
+/// sent by clients written before updates carried metadata.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BOMUpdateBody {
+    Events(Vec<BOMChangeRequest>),
+    Update {
+        events: Vec<BOMChangeRequest>,
+        #[serde(default)]
+        expected_version: Option<i32>,
+        #[serde(flatten)]
+        metadata: VersionMetadata,
+    },
+}
+
+impl From<BOMUpdateBody> for BOMUpdate {
+    fn from(value: BOMUpdateBody) -> Self {
+        match value {
+            BOMUpdateBody::Events(events) => BOMUpdate::new(events),
+            BOMUpdateBody::Update {
+                events,
+                expected_version,
+                metadata,
+            } => Self {
+                events,
+                expected_version,
+                metadata,
+            },
+        }
+    }
+}
+
+impl Display for BOMUpdate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.events)
+    }
+}
+
+impl BOMUpdate {
+    pub fn new(events: Vec<impl Into<BOMChangeRequest>>) -> Self {
+        Self {
+            events: events.into_iter().map(Into::into).collect(),
+            expected_version: None,
+            metadata: VersionMetadata::default(),
+        }
+    }
+
+    pub fn expecting_version(mut self, version: i32) -> Self {
+        self.expected_version = Some(version);
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: VersionMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_bare_event_list() {
+        let update: BOMUpdate = serde_json::from_value(json!([
+            { "type": "name_changed", "data": "BOM" }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            update,
+            BOMUpdate::new(vec![BOMChangeRequest::NameChanged("BOM".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_deserialize_update_with_metadata() {
+        let update: BOMUpdate = serde_json::from_value(json!({
+            "events": [{ "type": "name_changed", "data": "BOM" }],
+            "expected_version": 3,
+            "author": "jane.doe"
+        }))
+        .unwrap();
+
+        assert_eq!(update.expected_version, Some(3));
+        assert_eq!(update.metadata.author, Some("jane.doe".to_string()));
+        assert_eq!(update.events.len(), 1);
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::BOMChangeEvent;
+
+/// Points at a catalog component by id or by part number. A full component
+/// is read as a reference by its id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ComponentReference {
+    Id { id: Uuid },
+    PartNumber { part_number: String },
+}
+
+impl Display for ComponentReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentReference::Id { id } => write!(f, "id {}", id),
+            ComponentReference::PartNumber { part_number } => {
+                write!(f, "part number {}", part_number)
+            }
+        }
+    }
+}
+
+/// A change as submitted by a client. Components are only referenced and
+/// resolved against the catalog before the change becomes a `BOMChangeEvent`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+pub enum BOMChangeRequest {
+    NameChanged(String),
+    DescriptionChanged(String),
+    DescriptionCleared,
+    ComponentAdded(ComponentReference, i32),
+    ComponentRemoved(ComponentReference),
+    ComponentUpdated(Uuid, i32),
+    ComponentReplaced {
+        old: ComponentReference,
+        new: ComponentReference,
+        qty: i32,
+    },
+}
+
+impl From<BOMChangeEvent> for BOMChangeRequest {
+    fn from(value: BOMChangeEvent) -> Self {
+        match value {
+            BOMChangeEvent::NameChanged(name) => BOMChangeRequest::NameChanged(name),
+            BOMChangeEvent::DescriptionChanged(description) => {
+                BOMChangeRequest::DescriptionChanged(description)
+            }
+            BOMChangeEvent::DescriptionCleared => BOMChangeRequest::DescriptionCleared,
+            BOMChangeEvent::ComponentAdded(component, qty) => {
+                BOMChangeRequest::ComponentAdded(ComponentReference::Id { id: component.id }, qty)
+            }
+            BOMChangeEvent::ComponentRemoved(component) => {
+                BOMChangeRequest::ComponentRemoved(ComponentReference::Id { id: component.id })
+            }
+            BOMChangeEvent::ComponentUpdated(id, qty) => {
+                BOMChangeRequest::ComponentUpdated(id, qty)
+            }
+            BOMChangeEvent::ComponentReplaced { old, new, qty } => {
+                BOMChangeRequest::ComponentReplaced {
+                    old: ComponentReference::Id { id: old.id },
+                    new: ComponentReference::Id { id: new.id },
+                    qty,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_references() {
+        let id = Uuid::new_v4();
+
+        let requests: Vec<BOMChangeRequest> = serde_json::from_value(json!([
+            { "type": "component_added", "data": [{ "id": id }, 2] },
+            { "type": "component_removed", "data": { "part_number": "PRT-1" } },
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            requests,
+            vec![
+                BOMChangeRequest::ComponentAdded(ComponentReference::Id { id }, 2),
+                BOMChangeRequest::ComponentRemoved(ComponentReference::PartNumber {
+                    part_number: "PRT-1".to_string()
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_full_component_as_id_reference() {
+        let id = Uuid::new_v4();
+
+        let request: BOMChangeRequest = serde_json::from_value(json!({
+            "type": "component_added",
+            "data": [{
+                "id": id,
+                "name": "Component",
+                "part_number": "PRT-1",
+                "description": null,
+                "supplier": "Supplier",
+                "price": { "value": 10.0, "currency": "USD" }
+            }, 1]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            request,
+            BOMChangeRequest::ComponentAdded(ComponentReference::Id { id }, 1)
+        );
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::VersionMetadata;
+
+/// Asks for the events of `version` of another BOM to be applied again.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct CherryPick {
+    pub source_bom_id: Uuid,
+    pub version: i32,
+    /// Also apply changes of the name and description of the source.
+    #[serde(default)]
+    pub include_header_changes: bool,
+    #[serde(flatten)]
+    pub metadata: VersionMetadata,
+}
@@ -0,0 +1,66 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::domain::error::DomainError;
+
+/// A client supplied `Idempotency-Key` together with a fingerprint of the
+/// request it came with.
+#[derive(Debug, PartialEq, Clone)]
+pub struct IdempotencyKey {
+    pub key: String,
+    pub request_hash: String,
+}
+
+impl IdempotencyKey {
+    /// `scope` identifies the endpoint and resource, so that reusing a key for
+    /// another request counts as a conflicting payload.
+    pub fn new(key: &str, scope: &str, payload: &impl Serialize) -> Result<Self, DomainError> {
+        let key = key.trim();
+        if key.is_empty() || key.len() > 255 {
+            return Err(DomainError::ValidationError(
+                "Idempotency-Key must be between 1 and 255 characters".to_string(),
+            ));
+        }
+
+        let payload = serde_json::to_vec(payload)
+            .map_err(|error| DomainError::ConversionError(error.to_string()))?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(scope.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(&payload);
+
+        Ok(Self {
+            key: key.to_string(),
+            request_hash: format!("{:x}", hasher.finalize()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_request_has_same_hash() {
+        let first = IdempotencyKey::new("key", "POST /boms", &vec![1, 2]).unwrap();
+        let second = IdempotencyKey::new("key", "POST /boms", &vec![1, 2]).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_different_payload_or_scope_changes_hash() {
+        let original = IdempotencyKey::new("key", "POST /boms", &vec![1, 2]).unwrap();
+        let other_payload = IdempotencyKey::new("key", "POST /boms", &vec![1, 3]).unwrap();
+        let other_scope = IdempotencyKey::new("key", "PUT /boms/1", &vec![1, 2]).unwrap();
+
+        assert_ne!(original.request_hash, other_payload.request_hash);
+        assert_ne!(original.request_hash, other_scope.request_hash);
+    }
+
+    #[test]
+    fn test_empty_key_is_rejected() {
+        assert!(IdempotencyKey::new("  ", "POST /boms", &vec![1]).is_err());
+    }
+}
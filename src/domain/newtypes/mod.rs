@@ -1,2 +1,9 @@
+pub mod apply_diff;
+pub mod bom_patch;
+pub mod bom_reference;
+pub mod bom_update;
+pub mod change_request;
+pub mod cherry_pick;
+pub mod idempotency_key;
 pub mod new_bom;
 pub mod new_component;
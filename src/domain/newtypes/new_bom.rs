@@ -2,11 +2,21 @@ use std::fmt::{self, Display, Formatter};
 
 use serde::{Deserialize, Serialize};
 
-use crate::domain::BOMChangeEvent;
+use crate::domain::VersionMetadata;
+
+use super::change_request::BOMChangeRequest;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct NewBOM {
-    pub events: Vec<BOMChangeEvent>,
+    pub events: Vec<BOMChangeRequest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Rule set of a BOM whose category has none of its own. Any other rule
+    /// set than the one of the category is rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_set: Option<String>,
+    #[serde(flatten)]
+    pub metadata: VersionMetadata,
 }
 
 impl Display for NewBOM {
@@ -16,7 +26,27 @@ impl Display for NewBOM {
 }
 
 impl NewBOM {
-    pub fn new(events: Vec<BOMChangeEvent>) -> Self {
-        Self { events }
+    pub fn new(events: Vec<impl Into<BOMChangeRequest>>) -> Self {
+        Self {
+            events: events.into_iter().map(Into::into).collect(),
+            category: None,
+            rule_set: None,
+            metadata: VersionMetadata::default(),
+        }
+    }
+
+    pub fn in_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    pub fn with_rule_set(mut self, rule_set: &str) -> Self {
+        self.rule_set = Some(rule_set.to_string());
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: VersionMetadata) -> Self {
+        self.metadata = metadata;
+        self
     }
 }
@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::DomainError,
+    validation::{validate_write, Validator},
+    BOMChangeEvent, BOM,
+};
+
+/// A policy check configured for a rule set. Violations report its `id`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Rule {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: RuleKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum RuleKind {
+    /// No line may have more than `max` units.
+    MaxQuantity {
+        max: i32,
+    },
+    /// Components of these suppliers cannot be added.
+    ForbiddenSuppliers {
+        suppliers: Vec<String>,
+    },
+    /// Every component has to be priced in the same currency.
+    SingleCurrency,
+    RequiredDescription,
+    /// Names and descriptions may only contain alphanumeric characters,
+    /// whitespace and the characters in `allowed`.
+    AllowedCharacters {
+        allowed: String,
+    },
+    /// Names and descriptions may not contain any of the characters in
+    /// `forbidden`.
+    ForbiddenCharacters {
+        forbidden: String,
+    },
+}
+
+/// A rule broken by a write. `index` points at the offending event for rules
+/// that look at single events.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleViolation {
+    pub rule_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    pub message: String,
+}
+
+impl Validator<BOMChangeEvent> for Rule {
+    fn validate(&self, event: &BOMChangeEvent) -> Result<(), DomainError> {
+        let message = match (&self.kind, event) {
+            (
+                RuleKind::MaxQuantity { max },
+                BOMChangeEvent::ComponentAdded(_, qty)
+                | BOMChangeEvent::ComponentUpdated(_, qty)
+                | BOMChangeEvent::ComponentReplaced { qty, .. },
+            ) if qty > max => Some(format!(
+                "Quantity {} exceeds the maximum of {} per line",
+                qty, max
+            )),
+            (
+                RuleKind::ForbiddenSuppliers { suppliers },
+                BOMChangeEvent::ComponentAdded(component, _)
+                | BOMChangeEvent::ComponentReplaced { new: component, .. },
+            ) if suppliers.contains(&component.supplier) => Some(format!(
+                "Components of supplier {} are not allowed",
+                component.supplier
+            )),
+            (
+                RuleKind::AllowedCharacters { allowed },
+                BOMChangeEvent::NameChanged(text) | BOMChangeEvent::DescriptionChanged(text),
+            ) => text
+                .chars()
+                .find(|c| !c.is_alphanumeric() && !c.is_whitespace() && !allowed.contains(*c))
+                .map(|c| format!("Character {:?} is not allowed", c)),
+            (
+                RuleKind::ForbiddenCharacters { forbidden },
+                BOMChangeEvent::NameChanged(text) | BOMChangeEvent::DescriptionChanged(text),
+            ) => text
+                .chars()
+                .find(|c| forbidden.contains(*c))
+                .map(|c| format!("Character {:?} is not allowed", c)),
+            _ => None,
+        };
+
+        message.map_or(Ok(()), |message| Err(DomainError::ValidationError(message)))
+    }
+}
+
+impl Validator<BOM> for Rule {
+    fn validate(&self, bom: &BOM) -> Result<(), DomainError> {
+        let message = match &self.kind {
+            RuleKind::SingleCurrency => {
+                let mut currencies: Vec<&str> = bom
+                    .components
+                    .iter()
+                    .map(|cc| cc.component.price.currency.as_str())
+                    .collect();
+                currencies.sort_unstable();
+                currencies.dedup();
+
+                (currencies.len() > 1).then(|| {
+                    format!(
+                        "Components are priced in several currencies: {}",
+                        currencies.join(", ")
+                    )
+                })
+            }
+            RuleKind::RequiredDescription => bom
+                .description
+                .is_none()
+                .then(|| "A description is required".to_string()),
+            _ => None,
+        };
+
+        message.map_or(Ok(()), |message| Err(DomainError::ValidationError(message)))
+    }
+}
+
+/// Checks every event of a write and `bom`, the state the write leads to,
+/// against `rules`.
+pub fn check_rules(rules: &[Rule], events: &[BOMChangeEvent], bom: &BOM) -> Vec<RuleViolation> {
+    validate_write(rules, events, bom)
+        .into_iter()
+        .map(|(rule, index, error)| RuleViolation {
+            rule_id: rule.id.clone(),
+            index,
+            message: error.into_message(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::domain::{Component, CountedComponent, Price};
+
+    use super::*;
+
+    fn create_test_component(supplier: &str, currency: &str) -> Component {
+        Component {
+            id: Uuid::new_v4(),
+            name: "Component".to_string(),
+            part_number: "PRT-1".to_string(),
+            description: None,
+            supplier: supplier.to_string(),
+            price: Price {
+                value: 10.0,
+                currency: currency.to_string(),
+            },
+        }
+    }
+
+    fn rule(id: &str, kind: RuleKind) -> Rule {
+        Rule {
+            id: id.to_string(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_event_rules_report_rule_id_and_index() {
+        let rules = vec![
+            rule("max-quantity", RuleKind::MaxQuantity { max: 10 }),
+            rule(
+                "forbidden-suppliers",
+                RuleKind::ForbiddenSuppliers {
+                    suppliers: vec!["Blocked".to_string()],
+                },
+            ),
+            rule(
+                "allowed-characters",
+                RuleKind::AllowedCharacters {
+                    allowed: "-".to_string(),
+                },
+            ),
+        ];
+        let events = vec![
+            BOMChangeEvent::NameChanged("Main board - rev 2".to_string()),
+            BOMChangeEvent::ComponentAdded(create_test_component("Blocked", "EUR"), 11),
+            BOMChangeEvent::DescriptionChanged("Board_rev".to_string()),
+        ];
+
+        let violations = check_rules(&rules, &events, &BOM::default());
+
+        assert_eq!(
+            violations
+                .iter()
+                .map(|violation| (violation.rule_id.as_str(), violation.index))
+                .collect::<Vec<_>>(),
+            vec![
+                ("max-quantity", Some(1)),
+                ("forbidden-suppliers", Some(1)),
+                ("allowed-characters", Some(2)),
+            ]
+        );
+        assert_eq!(violations[2].message, "Character '_' is not allowed");
+    }
+
+    #[test]
+    fn test_forbidden_characters_only_reject_listed_characters() {
+        let rules = vec![rule(
+            "forbidden-characters",
+            RuleKind::ForbiddenCharacters {
+                forbidden: "/<>".to_string(),
+            },
+        )];
+        let events = vec![
+            BOMChangeEvent::NameChanged("Board_rev (2)".to_string()),
+            BOMChangeEvent::DescriptionChanged("Board <rev>".to_string()),
+        ];
+
+        let violations = check_rules(&rules, &events, &BOM::default());
+
+        assert_eq!(
+            violations,
+            vec![RuleViolation {
+                rule_id: "forbidden-characters".to_string(),
+                index: Some(1),
+                message: "Character '<' is not allowed".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_bom_rules_check_resulting_state() {
+        let rules = vec![
+            rule("single-currency", RuleKind::SingleCurrency),
+            rule("required-description", RuleKind::RequiredDescription),
+        ];
+        let mut bom = BOM::default();
+        bom.components.extend([
+            CountedComponent::new(create_test_component("Supplier", "USD"), 1),
+            CountedComponent::new(create_test_component("Supplier", "EUR"), 1),
+        ]);
+
+        let violations = check_rules(&rules, &[], &bom);
+
+        assert_eq!(
+            violations,
+            vec![
+                RuleViolation {
+                    rule_id: "single-currency".to_string(),
+                    index: None,
+                    message: "Components are priced in several currencies: EUR, USD".to_string(),
+                },
+                RuleViolation {
+                    rule_id: "required-description".to_string(),
+                    index: None,
+                    message: "A description is required".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rule_deserializes_from_flat_map() {
+        let rule: Rule = serde_json::from_value(serde_json::json!({
+            "id": "max-quantity",
+            "type": "max_quantity",
+            "max": 100
+        }))
+        .unwrap();
+
+        assert_eq!(rule.kind, RuleKind::MaxQuantity { max: 100 });
+    }
+}
@@ -0,0 +1,120 @@
+use serde_json::Value;
+
+use super::error::DomainError;
+
+/// Rewrites one stored payload from schema `n` to `n + 1`.
+pub type Upcaster = fn(Value) -> Result<Value, DomainError>;
+
+/// Upcasters in schema order: the one at index `i` migrates payloads written
+/// with schema `i + 1`. A change to the shape of `BOMChangeEvent` or
+/// `Component` that old payloads no longer deserialize into must append one.
+const UPCASTERS: &[Upcaster] = &[];
+
+/// Upcasters of stored BOMs, i.e. snapshots and the responses kept for
+/// idempotent replays. They follow the same schema versions as `UPCASTERS`
+/// and must keep fields they do not know about, such as version metadata.
+const BOM_UPCASTERS: &[Upcaster] = &[];
+
+const _: () = assert!(UPCASTERS.len() == BOM_UPCASTERS.len());
+
+/// The schema version written with every new version, snapshot and stored
+/// response of a BOM.
+pub const CURRENT_EVENT_SCHEMA_VERSION: i32 = UPCASTERS.len() as i32 + 1;
+
+/// Migrates changes stored with `schema_version` to the current schema.
+pub fn upcast_events(changes: Value, schema_version: i32) -> Result<Value, DomainError> {
+    upcast_with(UPCASTERS, changes, schema_version)
+}
+
+/// Migrates a BOM stored with `schema_version` to the current schema.
+pub fn upcast_bom(bom: Value, schema_version: i32) -> Result<Value, DomainError> {
+    upcast_with(BOM_UPCASTERS, bom, schema_version)
+}
+
+fn upcast_with(
+    upcasters: &[Upcaster],
+    changes: Value,
+    schema_version: i32,
+) -> Result<Value, DomainError> {
+    let latest = upcasters.len() as i32 + 1;
+
+    if !(1..=latest).contains(&schema_version) {
+        return Err(DomainError::ConversionError(format!(
+            "Unknown event schema version {}, latest is {}",
+            schema_version, latest
+        )));
+    }
+
+    upcasters[(schema_version - 1) as usize..]
+        .iter()
+        .try_fold(changes, |changes, upcaster| upcaster(changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rename_type(from: &'static str, to: &'static str) -> impl Fn(Value) -> Value {
+        move |mut changes| {
+            if let Some(events) = changes.as_array_mut() {
+                for event in events.iter_mut().filter(|event| event["type"] == from) {
+                    event["type"] = json!(to);
+                }
+            }
+            changes
+        }
+    }
+
+    fn first(changes: Value) -> Result<Value, DomainError> {
+        Ok(rename_type("name_set", "name_renamed")(changes))
+    }
+
+    fn second(changes: Value) -> Result<Value, DomainError> {
+        Ok(rename_type("name_renamed", "name_changed")(changes))
+    }
+
+    #[test]
+    fn test_upcast_applies_every_later_upcaster_in_order() {
+        let stored = json!([{ "type": "name_set", "data": "BOM" }]);
+
+        let upcasted = upcast_with(&[first, second], stored, 1).unwrap();
+
+        assert_eq!(upcasted, json!([{ "type": "name_changed", "data": "BOM" }]));
+    }
+
+    #[test]
+    fn test_upcast_skips_upcasters_older_than_the_stored_schema() {
+        let stored = json!([{ "type": "name_set", "data": "BOM" }]);
+
+        let upcasted = upcast_with(&[first, second], stored.clone(), 3).unwrap();
+
+        assert_eq!(upcasted, stored);
+    }
+
+    #[test]
+    fn test_upcast_rejects_unknown_schema_versions() {
+        assert!(upcast_with(&[first], json!([]), 0).is_err());
+        assert!(upcast_with(&[first], json!([]), 3).is_err());
+    }
+
+    #[test]
+    fn test_current_payloads_deserialize_without_upcasting() {
+        let stored = json!([{ "type": "name_changed", "data": "BOM" }]);
+
+        let upcasted = upcast_events(stored.clone(), CURRENT_EVENT_SCHEMA_VERSION).unwrap();
+
+        assert_eq!(upcasted, stored);
+    }
+
+    #[test]
+    fn test_current_boms_deserialize_without_upcasting() {
+        let stored = json!({ "name": "BOM", "components": [] });
+
+        let upcasted = upcast_bom(stored.clone(), CURRENT_EVENT_SCHEMA_VERSION).unwrap();
+
+        assert_eq!(upcasted, stored);
+        assert!(upcast_bom(stored, CURRENT_EVENT_SCHEMA_VERSION + 1).is_err());
+    }
+}
@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
 use unicode_segmentation::UnicodeSegmentation;
+use uuid::Uuid;
 
-use super::{error::DomainError, BOMChangeEvent};
+use super::{error::DomainError, BOMChangeEvent, BOM};
 
 pub trait Validator<T> {
     fn validate(&self, data: &T) -> Result<(), DomainError>;
@@ -47,16 +49,153 @@ impl Validator<BOMChangeEvent> for BOMChangeEventValidator {
                     ))
                 }
             }
-            BOMChangeEvent::ComponentRemoved(_) => Ok(()),
+            BOMChangeEvent::DescriptionCleared | BOMChangeEvent::ComponentRemoved(_) => Ok(()),
+            BOMChangeEvent::ComponentReplaced { old, new, qty } => {
+                if old.id == new.id {
+                    Err(DomainError::ValidationError(
+                        "A component cannot replace itself".to_string(),
+                    ))
+                } else if *qty > 0 {
+                    Ok(())
+                } else {
+                    Err(DomainError::ValidationError(
+                        "Quantity must be greater than 0".to_string(),
+                    ))
+                }
+            }
         }
     }
 }
 
-fn is_valid_string(s: &str) -> bool {
-    let forbidden_characters = ['/', '(', ')', '"', '<', '>', '\\', '{', '}'];
-    let contains_forbidden_characters = s.chars().any(|g| forbidden_characters.contains(&g));
+/// An event that cannot be applied, with its position in the submitted list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedEvent {
+    pub index: usize,
+    pub event: BOMChangeEvent,
+    pub reason: String,
+}
+
+impl RejectedEvent {
+    fn new(index: usize, event: &BOMChangeEvent, error: DomainError) -> Self {
+        Self {
+            index,
+            event: event.clone(),
+            reason: error.into_message(),
+        }
+    }
+}
+
+/// Runs `validators` over every event of a write, then over `bom`, the state
+/// the write leads to. Each failure comes with the validator that raised it
+/// and, for failures of single events, the position of the event.
+pub fn validate_write<'a, V>(
+    validators: &'a [V],
+    events: &[BOMChangeEvent],
+    bom: &BOM,
+) -> Vec<(&'a V, Option<usize>, DomainError)>
+where
+    V: Validator<BOMChangeEvent> + Validator<BOM>,
+{
+    let event_failures = events.iter().enumerate().flat_map(|(index, event)| {
+        validators.iter().filter_map(move |validator| {
+            Validator::<BOMChangeEvent>::validate(validator, event)
+                .err()
+                .map(|error| (validator, Some(index), error))
+        })
+    });
+
+    let state_failures = validators.iter().filter_map(|validator| {
+        Validator::<BOM>::validate(validator, bom)
+            .err()
+            .map(|error| (validator, None, error))
+    });
+
+    event_failures.chain(state_failures).collect()
+}
+
+/// Checks every event against the state `bom` will be in by the time the
+/// event is applied. Adding a component that is already part of the BOM is
+/// merged into an update of its quantity. Fails with every event that cannot
+/// be applied.
+pub fn normalize_events(
+    bom: &BOM,
+    events: &[BOMChangeEvent],
+) -> Result<Vec<BOMChangeEvent>, Vec<RejectedEvent>> {
+    let mut bom = bom.clone();
+    let mut normalized = Vec::with_capacity(events.len());
+    let mut rejected = vec![];
+
+    for (index, event) in events.iter().enumerate() {
+        let result = BOMChangeEventValidator
+            .validate(event)
+            .and_then(|_| normalize_against_state(&bom, event))
+            .and_then(|event| {
+                bom.apply_change(&event, BOMChangeEventValidator)?;
+                Ok(event)
+            });
+
+        match result {
+            Ok(event) => normalized.push(event),
+            Err(error) => rejected.push(RejectedEvent::new(index, event, error)),
+        }
+    }
+
+    if rejected.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(rejected)
+    }
+}
+
+fn normalize_against_state(
+    bom: &BOM,
+    event: &BOMChangeEvent,
+) -> Result<BOMChangeEvent, DomainError> {
+    let quantity_of = |id: &Uuid| {
+        bom.components
+            .iter()
+            .find(|cc| cc.component.id == *id)
+            .map(|cc| cc.quantity)
+    };
+
+    match event {
+        BOMChangeEvent::ComponentAdded(component, qty) => match quantity_of(&component.id) {
+            Some(existing) => Ok(BOMChangeEvent::ComponentUpdated(
+                component.id,
+                existing.checked_add(*qty).ok_or_else(|| {
+                    DomainError::ValidationError(format!(
+                        "Quantity of component {} is too large",
+                        component.id
+                    ))
+                })?,
+            )),
+            None => Ok(event.clone()),
+        },
+        BOMChangeEvent::ComponentUpdated(id, _) if quantity_of(id).is_none() => Err(
+            DomainError::ValidationError(format!("Component {} is not part of the BOM", id)),
+        ),
+        BOMChangeEvent::ComponentRemoved(component) if quantity_of(&component.id).is_none() => {
+            Err(DomainError::ValidationError(format!(
+                "Component {} is not part of the BOM",
+                component.id
+            )))
+        }
+        BOMChangeEvent::ComponentReplaced { old, .. } if quantity_of(&old.id).is_none() => Err(
+            DomainError::ValidationError(format!("Component {} is not part of the BOM", old.id)),
+        ),
+        BOMChangeEvent::ComponentReplaced { new, .. } if quantity_of(&new.id).is_some() => {
+            Err(DomainError::ValidationError(format!(
+                "Component {} is already part of the BOM",
+                new.id
+            )))
+        }
+        _ => Ok(event.clone()),
+    }
+}
 
-    !s.trim().is_empty() && s.graphemes(true).count() < 255 && !contains_forbidden_characters
+/// Which characters are allowed is up to the rule set of the BOM.
+fn is_valid_string(s: &str) -> bool {
+    !s.trim().is_empty() && s.graphemes(true).count() < 255
 }
 
 #[cfg(test)]
@@ -84,15 +223,7 @@ mod tests {
     #[test]
     fn test_is_valid_string() {
         assert!(is_valid_string("valid string"));
-        assert!(!is_valid_string("invalid string/"));
-        assert!(!is_valid_string("invalid string("));
-        assert!(!is_valid_string("invalid string)"));
-        assert!(!is_valid_string("invalid string\""));
-        assert!(!is_valid_string("invalid string<"));
-        assert!(!is_valid_string("invalid string>"));
-        assert!(!is_valid_string("invalid string\\"));
-        assert!(!is_valid_string("invalid string{"));
-        assert!(!is_valid_string("invalid string}"));
+        assert!(is_valid_string("valid string (rev. 2/3)"));
         assert!(!is_valid_string("iiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiiii"));
         assert!(!is_valid_string(" "));
         assert!(!is_valid_string(""));
@@ -102,7 +233,7 @@ mod tests {
     fn test_validate_name() {
         let validator = BOMChangeEventValidator;
         let name = "valid name".to_string();
-        let invalid_name = "invalid name/".to_string();
+        let invalid_name = " ".to_string();
 
         assert_eq!(
             validator.validate(&BOMChangeEvent::NameChanged(name)),
@@ -120,7 +251,7 @@ mod tests {
     fn test_validate_description() {
         let validator = BOMChangeEventValidator;
         let description = "valid description".to_string();
-        let invalid_description = "invalid description/".to_string();
+        let invalid_description = "".to_string();
 
         assert_eq!(
             validator.validate(&BOMChangeEvent::DescriptionChanged(description)),
@@ -186,6 +317,129 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_events_reports_every_failing_event() {
+        let events = vec![
+            BOMChangeEvent::NameChanged("".to_string()),
+            BOMChangeEvent::NameChanged("Name".to_string()),
+            BOMChangeEvent::ComponentAdded(create_test_component(), 0),
+        ];
+
+        let rejected = normalize_events(&BOM::default(), &events).unwrap_err();
+
+        assert_eq!(
+            rejected,
+            vec![
+                RejectedEvent {
+                    index: 0,
+                    event: events[0].clone(),
+                    reason: "Invalid name input".to_string(),
+                },
+                RejectedEvent {
+                    index: 2,
+                    event: events[2].clone(),
+                    reason: "Quantity must be greater than 0".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_events_checks_against_current_state() {
+        let present = create_test_component();
+        let missing = create_test_component();
+        let mut bom = BOM::default();
+        bom.components
+            .push(crate::domain::CountedComponent::new(present.clone(), 1));
+
+        let events = vec![
+            BOMChangeEvent::ComponentUpdated(present.id, 2),
+            BOMChangeEvent::ComponentUpdated(missing.id, 2),
+            BOMChangeEvent::ComponentRemoved(present.clone()),
+            BOMChangeEvent::ComponentRemoved(present.clone()),
+            BOMChangeEvent::ComponentAdded(missing.clone(), 0),
+        ];
+
+        let rejected = normalize_events(&bom, &events).unwrap_err();
+
+        assert_eq!(
+            rejected
+                .iter()
+                .map(|rejected| rejected.index)
+                .collect::<Vec<_>>(),
+            vec![1, 3, 4]
+        );
+        assert_eq!(
+            rejected[0].reason,
+            format!("Component {} is not part of the BOM", missing.id)
+        );
+        assert_eq!(rejected[2].reason, "Quantity must be greater than 0");
+    }
+
+    #[test]
+    fn test_normalize_events_merges_duplicate_adds_into_updates() {
+        let present = create_test_component();
+        let added = create_test_component();
+        let mut bom = BOM::default();
+        bom.components
+            .push(crate::domain::CountedComponent::new(present.clone(), 2));
+
+        let events = vec![
+            BOMChangeEvent::ComponentAdded(present.clone(), 3),
+            BOMChangeEvent::ComponentAdded(added.clone(), 1),
+            BOMChangeEvent::ComponentAdded(added.clone(), 4),
+        ];
+
+        let normalized = normalize_events(&bom, &events).unwrap();
+
+        assert_eq!(
+            normalized,
+            vec![
+                BOMChangeEvent::ComponentUpdated(present.id, 5),
+                BOMChangeEvent::ComponentAdded(added.clone(), 1),
+                BOMChangeEvent::ComponentUpdated(added.id, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_events_checks_replacements_against_current_state() {
+        let present = create_test_component();
+        let other = create_test_component();
+        let missing = create_test_component();
+        let mut bom = BOM::default();
+        bom.components.extend([
+            crate::domain::CountedComponent::new(present.clone(), 1),
+            crate::domain::CountedComponent::new(other.clone(), 1),
+        ]);
+
+        let replaced = |old: &Component, new: &Component| BOMChangeEvent::ComponentReplaced {
+            old: old.clone(),
+            new: new.clone(),
+            qty: 1,
+        };
+        let events = vec![
+            replaced(&missing, &present),
+            replaced(&present, &other),
+            replaced(&present, &present),
+            replaced(&present, &missing),
+        ];
+
+        let rejected = normalize_events(&bom, &events).unwrap_err();
+
+        assert_eq!(
+            rejected
+                .iter()
+                .map(|rejected| rejected.reason.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                format!("Component {} is not part of the BOM", missing.id),
+                format!("Component {} is already part of the BOM", other.id),
+                "A component cannot replace itself".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn test_validate_component_removed() {
         let validator = BOMChangeEventValidator;
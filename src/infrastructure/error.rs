@@ -1,10 +1,38 @@
+use diesel::result::DatabaseErrorKind;
+
 use super::aliases::DieselError;
 use r2d2;
+use uuid::Uuid;
+
+const BOM_VERSION_CONSTRAINT: &str = "bom_versions_bom_id_version_key";
 
 #[derive(Debug)]
 pub enum DatabaseError {
     DieselError(diesel::result::Error),
     R2D2Error(r2d2::Error),
+    /// The BOM was changed by someone else; holds its current version.
+    StaleVersion(i32),
+    /// The BOM was archived before the write could be stored.
+    Archived(Uuid),
+}
+
+impl DatabaseError {
+    /// Whether the write lost a race against a concurrent one and may succeed
+    /// when retried on top of the latest state.
+    pub fn is_write_conflict(&self) -> bool {
+        match self {
+            DatabaseError::StaleVersion(_) => true,
+            DatabaseError::DieselError(DieselError::DatabaseError(kind, info)) => match kind {
+                DatabaseErrorKind::SerializationFailure => true,
+                // Another writer stored the same version first
+                DatabaseErrorKind::UniqueViolation => {
+                    info.constraint_name() == Some(BOM_VERSION_CONSTRAINT)
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
 }
 
 impl From<DieselError> for DatabaseError {
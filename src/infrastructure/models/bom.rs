@@ -13,4 +13,8 @@ pub struct BOM {
     pub version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub category: Option<String>,
+    pub rule_set: Option<String>,
 }
@@ -16,14 +16,17 @@ pub struct BomComponent {
     pub bom_id: Uuid,
     pub component_id: Uuid,
     pub quantity: i32,
+    /// Index of the line within the BOM.
+    pub position: i32,
 }
 
 impl BomComponent {
-    pub fn new(bom_id: Uuid, component_id: Uuid, quantity: i32) -> Self {
+    pub fn new(bom_id: Uuid, component_id: Uuid, quantity: i32, position: i32) -> Self {
         Self {
             bom_id,
             component_id,
             quantity,
+            position,
         }
     }
 }
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use diesel::{prelude::Insertable, Identifiable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::bom_lineage;
+
+#[derive(
+    Debug, PartialEq, Serialize, Deserialize, Identifiable, Selectable, Queryable, Insertable,
+)]
+#[diesel(primary_key(bom_id))]
+#[diesel(table_name = bom_lineage)]
+pub struct BomLineage {
+    pub bom_id: Uuid,
+    pub source_bom_id: Uuid,
+    pub source_version: i32,
+    pub created_at: DateTime<Utc>,
+}
@@ -27,4 +27,11 @@ pub struct BomVersion {
     pub version: i32,
     pub changes: Value,
     pub created_at: DateTime<Utc>,
+    pub author: Option<String>,
+    pub message: Option<String>,
+    pub ticket: Option<String>,
+    pub reverted_to: Option<i32>,
+    pub tag: Option<String>,
+    pub squashed_from: Option<i32>,
+    pub schema_version: i32,
 }
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use diesel::{prelude::Insertable, AsChangeset, Identifiable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::component_lifecycles;
+
+#[derive(
+    Debug,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Identifiable,
+    Selectable,
+    Queryable,
+    Insertable,
+    AsChangeset,
+)]
+#[diesel(primary_key(component_id))]
+#[diesel(table_name = component_lifecycles)]
+pub struct ComponentLifecycle {
+    pub component_id: Uuid,
+    pub status: String,
+    pub updated_at: DateTime<Utc>,
+}
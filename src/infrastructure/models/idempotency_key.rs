@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use diesel::{prelude::Insertable, Identifiable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::schema::idempotency_keys;
+
+#[derive(
+    Debug, PartialEq, Serialize, Deserialize, Identifiable, Selectable, Queryable, Insertable,
+)]
+#[diesel(primary_key(key))]
+#[diesel(table_name = idempotency_keys)]
+pub struct IdempotencyKey {
+    pub key: String,
+    pub request_hash: String,
+    pub response: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub schema_version: i32,
+}
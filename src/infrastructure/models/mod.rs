@@ -1,4 +1,8 @@
 pub mod bom;
 pub mod bom_components;
+pub mod bom_lineage;
+pub mod bom_snapshot;
 pub mod bom_version;
 pub mod component;
+pub mod component_lifecycle;
+pub mod idempotency_key;
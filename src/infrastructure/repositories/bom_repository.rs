@@ -1,20 +1,33 @@
 use std::vec;
 
-use diesel::{ExpressionMethods, JoinOnDsl, PgConnection, QueryDsl, RunQueryDsl};
+use chrono::{DateTime, Utc};
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, JoinOnDsl, OptionalExtension, PgConnection, QueryDsl,
+    RunQueryDsl,
+};
+use serde_json::Value;
 use uuid::Uuid;
 
 use crate::{
     infrastructure::{
-        aliases::DbPool,
+        aliases::{DbPool, DieselError},
         error::DatabaseError,
         models::{
-            bom::BOM, bom_components::BomComponent, bom_version::BomVersion, component::Component,
+            bom::BOM, bom_components::BomComponent, bom_lineage::BomLineage,
+            bom_snapshot::BomSnapshot, bom_version::BomVersion, component::Component,
+            component_lifecycle::ComponentLifecycle, idempotency_key::IdempotencyKey,
         },
         repositories::repository::Repository,
     },
-    schema::{bom_versions, boms, boms_components, components},
+    schema::{
+        bom_lineage, bom_snapshots, bom_versions, boms, boms_components, component_lifecycles,
+        components, idempotency_keys,
+    },
 };
 
+/// Version, archiving and deletion time of a BOM locked for a write.
+type LockedBom = (i32, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
 pub struct BomRepository {
     pool: DbPool,
 }
@@ -26,11 +39,14 @@ impl BomRepository {
 }
 
 impl Repository for BomRepository {
-    fn find_all(&self) -> Result<Vec<(BOM, Vec<(Component, i32)>)>, DatabaseError> {
+    fn find_all(
+        &self,
+        include_archived: bool,
+    ) -> Result<Vec<(BOM, Vec<(Component, i32)>)>, DatabaseError> {
         let mut conn = self.pool.get()?;
 
         let mut result: Vec<(BOM, Vec<(Component, i32)>)> = vec![];
-        let boms = self.find_all_boms(&mut conn)?;
+        let boms = self.find_all_boms(include_archived, &mut conn)?;
 
         for bom in boms {
             let components = self.find_components_of_bom_by_bom_id(bom.id, &mut conn)?;
@@ -49,17 +65,84 @@ impl Repository for BomRepository {
         Ok((bom, components))
     }
 
+    fn find_deleted_by_id(&self, bom_id: Uuid) -> Result<BOM, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(boms::table
+            .find(bom_id)
+            .filter(boms::deleted_at.is_not_null())
+            .first::<BOM>(&mut conn)?)
+    }
+
+    fn set_archived_at(
+        &self,
+        bom_id: Uuid,
+        archived_at: Option<DateTime<Utc>>,
+    ) -> Result<BOM, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(
+            diesel::update(boms::table.find(bom_id).filter(boms::deleted_at.is_null()))
+                .set(boms::archived_at.eq(archived_at))
+                .get_result(&mut conn)?,
+        )
+    }
+
+    fn set_deleted_at(
+        &self,
+        bom_id: Uuid,
+        deleted_at: Option<DateTime<Utc>>,
+    ) -> Result<BOM, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(diesel::update(boms::table.find(bom_id))
+            .set(boms::deleted_at.eq(deleted_at))
+            .get_result(&mut conn)?)
+    }
+
+    fn purge(&self, bom_id: Uuid) -> Result<(), DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        diesel::delete(
+            boms::table
+                .find(bom_id)
+                .filter(boms::deleted_at.is_not_null()),
+        )
+        .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    fn purge_deleted_before(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(
+            diesel::delete(boms::table.filter(boms::deleted_at.lt(deleted_before)))
+                .returning(boms::id)
+                .get_results(&mut conn)?,
+        )
+    }
+
     fn insert(
         &self,
         new_bom: &BOM,
         new_bom_components: &[BomComponent],
         new_bom_version: &BomVersion,
+        lineage: Option<&BomLineage>,
     ) -> Result<(BOM, Vec<(Component, i32)>), DatabaseError> {
         let mut conn = self.pool.get()?;
         conn.build_transaction().run(|conn| {
             let created_bom = self.insert_bom(new_bom, conn)?;
             let _ = self.insert_bom_version(new_bom_version, conn)?;
             let _ = self.insert_bom_components(new_bom_components, conn)?;
+            if let Some(lineage) = lineage {
+                diesel::insert_into(bom_lineage::table)
+                    .values(lineage)
+                    .execute(conn)?;
+            }
             let components = self.find_components_of_bom_by_bom_id(created_bom.id, conn)?;
 
             Ok((created_bom, components))
@@ -69,17 +152,33 @@ impl Repository for BomRepository {
     fn update_and_archive(
         &self,
         bom_id: Uuid,
+        base_version: i32,
         updated_bom: &BOM,
         updated_bom_components: &[BomComponent],
         updated_bom_version: &BomVersion,
+        new_bom_snapshot: Option<&BomSnapshot>,
     ) -> Result<(BOM, Vec<(Component, i32)>), DatabaseError> {
         let mut conn = self.pool.get()?;
 
         conn.build_transaction().run(|conn| {
+            let (current_version, archived_at, deleted_at) = self.lock_bom_version(bom_id, conn)?;
+            if deleted_at.is_some() {
+                return Err(DieselError::NotFound.into());
+            }
+            if archived_at.is_some() {
+                return Err(DatabaseError::Archived(bom_id));
+            }
+            if current_version != base_version {
+                return Err(DatabaseError::StaleVersion(current_version));
+            }
+
             let updated_bom = self.update_bom_by_id(bom_id, updated_bom, conn)?;
             self.delete_bom_components_by_bom_id(bom_id, conn)?;
             let _ = self.insert_bom_components(updated_bom_components, conn)?;
             let _ = self.insert_bom_version(updated_bom_version, conn)?;
+            if let Some(new_bom_snapshot) = new_bom_snapshot {
+                let _ = self.insert_bom_snapshot(new_bom_snapshot, conn)?;
+            }
             let components = self.find_components_of_bom_by_bom_id(bom_id, conn)?;
 
             Ok((updated_bom, components))
@@ -103,6 +202,339 @@ impl Repository for BomRepository {
         Ok(versions)
     }
 
+    fn get_bom_versions_page(
+        &self,
+        bom_id: Uuid,
+        offset: i64,
+        limit: i64,
+    ) -> Result<(Vec<BomVersion>, i64), DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        let total = bom_versions::table
+            .filter(bom_versions::bom_id.eq(bom_id))
+            .count()
+            .get_result::<i64>(&mut conn)?;
+
+        let versions: Vec<BomVersion> = bom_versions::table
+            .filter(bom_versions::bom_id.eq(bom_id))
+            .order(bom_versions::version.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(bom_versions::all_columns)
+            .load(&mut conn)?;
+
+        Ok((versions, total))
+    }
+
+    fn find_bom_version(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+    ) -> Result<Option<BomVersion>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_versions::table
+            .filter(bom_versions::bom_id.eq(bom_id))
+            .filter(bom_versions::version.le(version))
+            .order(bom_versions::version.desc())
+            .select(bom_versions::all_columns)
+            .first::<BomVersion>(&mut conn)
+            .optional()?)
+    }
+
+    fn find_bom_version_as_of(
+        &self,
+        bom_id: Uuid,
+        instant: DateTime<Utc>,
+    ) -> Result<Option<BomVersion>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_versions::table
+            .filter(bom_versions::bom_id.eq(bom_id))
+            .filter(bom_versions::created_at.le(instant))
+            .order(bom_versions::version.desc())
+            .select(bom_versions::all_columns)
+            .first::<BomVersion>(&mut conn)
+            .optional()?)
+    }
+
+    fn get_bom_versions_after_version(
+        &self,
+        bom_id: Uuid,
+        after_version: i32,
+        until_version: i32,
+    ) -> Result<Vec<BomVersion>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        let versions: Vec<BomVersion> = bom_versions::table
+            .filter(bom_versions::bom_id.eq(bom_id))
+            .filter(bom_versions::version.gt(after_version))
+            .filter(bom_versions::version.le(until_version))
+            .order(bom_versions::version.asc())
+            .select(bom_versions::all_columns)
+            .load(&mut conn)?;
+
+        Ok(versions)
+    }
+
+    fn find_latest_snapshot_until_version(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+    ) -> Result<Option<BomSnapshot>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_snapshots::table
+            .filter(bom_snapshots::bom_id.eq(bom_id))
+            .filter(bom_snapshots::version.le(version))
+            .order(bom_snapshots::version.desc())
+            .first::<BomSnapshot>(&mut conn)
+            .optional()?)
+    }
+
+    fn find_snapshots(&self, bom_id: Uuid) -> Result<Vec<BomSnapshot>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_snapshots::table
+            .filter(bom_snapshots::bom_id.eq(bom_id))
+            .order(bom_snapshots::version.asc())
+            .load::<BomSnapshot>(&mut conn)?)
+    }
+
+    fn find_lineage(&self, bom_id: Uuid) -> Result<Option<BomLineage>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_lineage::table
+            .find(bom_id)
+            .first::<BomLineage>(&mut conn)
+            .optional()?)
+    }
+
+    fn find_clones_of(&self, source_bom_ids: &[Uuid]) -> Result<Vec<BomLineage>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_lineage::table
+            .filter(bom_lineage::source_bom_id.eq_any(source_bom_ids))
+            .order(bom_lineage::created_at.asc())
+            .load::<BomLineage>(&mut conn)?)
+    }
+
+    fn find_tagged_bom_versions(&self, bom_id: Uuid) -> Result<Vec<BomVersion>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_versions::table
+            .filter(bom_versions::bom_id.eq(bom_id))
+            .filter(bom_versions::tag.is_not_null())
+            .order(bom_versions::version.asc())
+            .select(bom_versions::all_columns)
+            .load::<BomVersion>(&mut conn)?)
+    }
+
+    fn tag_bom_version(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+        tag: &str,
+    ) -> Result<BomVersion, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(diesel::update(
+            bom_versions::table
+                .filter(bom_versions::bom_id.eq(bom_id))
+                .filter(bom_versions::version.eq(version)),
+        )
+        .set(bom_versions::tag.eq(tag))
+        .get_result(&mut conn)?)
+    }
+
+    fn squash_bom_versions(
+        &self,
+        bom_id: Uuid,
+        from: i32,
+        to: i32,
+        squash: &mut dyn FnMut(i32, Vec<BomVersion>) -> Result<BomVersion, DatabaseError>,
+    ) -> Result<BomVersion, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        conn.build_transaction().run(|conn| {
+            let (current_version, _, deleted_at) = self.lock_bom_version(bom_id, conn)?;
+            if deleted_at.is_some() {
+                return Err(DieselError::NotFound.into());
+            }
+
+            let versions = bom_versions::table
+                .filter(bom_versions::bom_id.eq(bom_id))
+                .filter(bom_versions::version.le(to))
+                .order(bom_versions::version.asc())
+                .load::<BomVersion>(conn)?;
+            let squashed = squash(current_version, versions)?;
+
+            diesel::delete(
+                bom_versions::table
+                    .filter(bom_versions::bom_id.eq(bom_id))
+                    .filter(bom_versions::version.ge(from))
+                    .filter(bom_versions::version.lt(squashed.version)),
+            )
+            .execute(conn)?;
+
+            diesel::delete(
+                bom_snapshots::table
+                    .filter(bom_snapshots::bom_id.eq(bom_id))
+                    .filter(bom_snapshots::version.ge(from))
+                    .filter(bom_snapshots::version.lt(squashed.version)),
+            )
+            .execute(conn)?;
+
+            Ok(diesel::update(
+                bom_versions::table
+                    .filter(bom_versions::bom_id.eq(bom_id))
+                    .filter(bom_versions::version.eq(squashed.version)),
+            )
+            .set((
+                bom_versions::changes.eq(&squashed.changes),
+                bom_versions::squashed_from.eq(squashed.squashed_from),
+                bom_versions::schema_version.eq(squashed.schema_version),
+            ))
+            .get_result(conn)?)
+        })
+    }
+
+    fn find_bom_versions_below_schema(
+        &self,
+        schema_version: i32,
+        limit: i64,
+    ) -> Result<Vec<BomVersion>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_versions::table
+            .filter(bom_versions::schema_version.lt(schema_version))
+            .order(bom_versions::id)
+            .limit(limit)
+            .load(&mut conn)?)
+    }
+
+    fn rewrite_bom_version_changes(
+        &self,
+        changes: &[(Uuid, Value)],
+        schema_version: i32,
+    ) -> Result<(), DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        conn.build_transaction().run(|conn| {
+            for (id, changes) in changes {
+                diesel::update(bom_versions::table.find(id))
+                    .set((
+                        bom_versions::changes.eq(changes),
+                        bom_versions::schema_version.eq(schema_version),
+                    ))
+                    .execute(conn)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn find_bom_snapshots_below_schema(
+        &self,
+        schema_version: i32,
+        limit: i64,
+    ) -> Result<Vec<BomSnapshot>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(bom_snapshots::table
+            .filter(bom_snapshots::schema_version.lt(schema_version))
+            .order(bom_snapshots::id)
+            .limit(limit)
+            .load(&mut conn)?)
+    }
+
+    fn rewrite_bom_snapshots(
+        &self,
+        snapshots: &[(Uuid, Value)],
+        schema_version: i32,
+    ) -> Result<(), DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        conn.build_transaction().run(|conn| {
+            for (id, snapshot) in snapshots {
+                diesel::update(bom_snapshots::table.find(id))
+                    .set((
+                        bom_snapshots::snapshot.eq(snapshot),
+                        bom_snapshots::schema_version.eq(schema_version),
+                    ))
+                    .execute(conn)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn reserve_idempotency_key(
+        &self,
+        key: &IdempotencyKey,
+        expired_before: DateTime<Utc>,
+        abandoned_before: DateTime<Utc>,
+    ) -> Result<Option<IdempotencyKey>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        conn.build_transaction().run(|conn| {
+            diesel::delete(
+                idempotency_keys::table.filter(
+                    idempotency_keys::created_at
+                        .lt(expired_before)
+                        .or(idempotency_keys::response
+                            .is_null()
+                            .and(idempotency_keys::created_at.lt(abandoned_before))),
+                ),
+            )
+            .execute(conn)?;
+
+            let inserted = diesel::insert_into(idempotency_keys::table)
+                .values(key)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            if inserted == 1 {
+                return Ok(None);
+            }
+
+            Ok(Some(
+                idempotency_keys::table
+                    .find(&key.key)
+                    .first::<IdempotencyKey>(conn)?,
+            ))
+        })
+    }
+
+    fn complete_idempotency_key(&self, key: &str, response: &Value) -> Result<(), DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        // A reservation that was taken over keeps the response of the request
+        // that took it
+        diesel::update(
+            idempotency_keys::table
+                .find(key)
+                .filter(idempotency_keys::response.is_null()),
+        )
+        .set(idempotency_keys::response.eq(response))
+        .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    fn release_idempotency_key(&self, key: &str) -> Result<(), DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        diesel::delete(
+            idempotency_keys::table
+                .find(key)
+                .filter(idempotency_keys::response.is_null()),
+        )
+        .execute(&mut conn)?;
+
+        Ok(())
+    }
+
     fn find_all_components(&self) -> Result<Vec<Component>, DatabaseError> {
         let mut conn = self.pool.get()?;
 
@@ -117,6 +549,47 @@ impl Repository for BomRepository {
             .first::<Component>(&mut conn)?)
     }
 
+    fn upsert_component_lifecycle(
+        &self,
+        lifecycle: &ComponentLifecycle,
+    ) -> Result<ComponentLifecycle, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(diesel::insert_into(component_lifecycles::table)
+            .values(lifecycle)
+            .on_conflict(component_lifecycles::component_id)
+            .do_update()
+            .set(lifecycle)
+            .get_result(&mut conn)?)
+    }
+
+    fn find_component_lifecycles(
+        &self,
+        component_ids: &[Uuid],
+    ) -> Result<Vec<ComponentLifecycle>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(component_lifecycles::table
+            .filter(component_lifecycles::component_id.eq_any(component_ids))
+            .load(&mut conn)?)
+    }
+
+    fn find_components_by_ids_or_part_numbers(
+        &self,
+        ids: &[Uuid],
+        part_numbers: &[String],
+    ) -> Result<Vec<Component>, DatabaseError> {
+        let mut conn = self.pool.get()?;
+
+        Ok(components::table
+            .filter(
+                components::id
+                    .eq_any(ids)
+                    .or(components::part_number.eq_any(part_numbers)),
+            )
+            .load(&mut conn)?)
+    }
+
     fn insert_component(&self, new_component: Component) -> Result<Component, DatabaseError> {
         let mut conn = self.pool.get()?;
 
@@ -149,12 +622,24 @@ impl Repository for BomRepository {
 }
 
 impl BomRepository {
-    fn find_all_boms(&self, conn: &mut PgConnection) -> Result<Vec<BOM>, DatabaseError> {
-        Ok(boms::table.load::<BOM>(conn)?)
+    fn find_all_boms(
+        &self,
+        include_archived: bool,
+        conn: &mut PgConnection,
+    ) -> Result<Vec<BOM>, DatabaseError> {
+        let mut query = boms::table.filter(boms::deleted_at.is_null()).into_boxed();
+        if !include_archived {
+            query = query.filter(boms::archived_at.is_null());
+        }
+
+        Ok(query.load::<BOM>(conn)?)
     }
 
     fn find_bom_by_id(&self, bom_id: Uuid, conn: &mut PgConnection) -> Result<BOM, DatabaseError> {
-        Ok(boms::table.find(bom_id).first::<BOM>(conn)?)
+        Ok(boms::table
+            .find(bom_id)
+            .filter(boms::deleted_at.is_null())
+            .first::<BOM>(conn)?)
     }
 
     fn find_components_of_bom_by_bom_id(
@@ -165,6 +650,7 @@ impl BomRepository {
         Ok(components::table
             .inner_join(boms_components::table.on(boms_components::component_id.eq(components::id)))
             .filter(boms_components::bom_id.eq(bom_id))
+            .order(boms_components::position.asc())
             .select((components::all_columns, boms_components::quantity))
             .load(conn)?)
     }
@@ -185,6 +671,16 @@ impl BomRepository {
             .get_result(conn)?)
     }
 
+    fn insert_bom_snapshot(
+        &self,
+        new_bom_snapshot: &BomSnapshot,
+        conn: &mut PgConnection,
+    ) -> Result<BomSnapshot, DatabaseError> {
+        Ok(diesel::insert_into(bom_snapshots::table)
+            .values(new_bom_snapshot)
+            .get_result(conn)?)
+    }
+
     fn insert_bom_components(
         &self,
         new_bom_components: &[BomComponent],
@@ -195,14 +691,37 @@ impl BomRepository {
             .get_results(conn)?)
     }
 
+    /// Locks the BOM row until the end of the transaction and returns its
+    /// version, so that concurrent writers are applied one after the other.
+    fn lock_bom_version(
+        &self,
+        bom_id: Uuid,
+        conn: &mut PgConnection,
+    ) -> Result<LockedBom, DatabaseError> {
+        Ok(boms::table
+            .find(bom_id)
+            .select((boms::version, boms::archived_at, boms::deleted_at))
+            .for_update()
+            .first(conn)?)
+    }
+
     fn update_bom_by_id(
         &self,
         bom_id: Uuid,
         updated_bom: &BOM,
         conn: &mut PgConnection,
     ) -> Result<BOM, DatabaseError> {
+        // Columns are assigned one by one so that a cleared field is written
+        // as NULL instead of being skipped. Archiving has its own
+        // statement and the category and rule set are fixed at creation, so a
+        // stale read of them is never written back
         Ok(diesel::update(boms::table.find(bom_id))
-            .set(updated_bom)
+            .set((
+                boms::name.eq(&updated_bom.name),
+                boms::description.eq(&updated_bom.description),
+                boms::version.eq(updated_bom.version),
+                boms::updated_at.eq(updated_bom.updated_at),
+            ))
             .get_result(conn)?)
     }
 
@@ -1,31 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
 use uuid::Uuid;
 
 use crate::infrastructure::{
     error::DatabaseError,
     models::{
-        bom::BOM, bom_components::BomComponent, bom_version::BomVersion, component::Component,
+        bom::BOM, bom_components::BomComponent, bom_lineage::BomLineage, bom_snapshot::BomSnapshot,
+        bom_version::BomVersion, component::Component, component_lifecycle::ComponentLifecycle,
+        idempotency_key::IdempotencyKey,
     },
 };
 
 pub trait Repository: Send + Sync + 'static {
     #[allow(clippy::type_complexity)]
-    fn find_all(&self) -> Result<Vec<(BOM, Vec<(Component, i32)>)>, DatabaseError>;
+    fn find_all(
+        &self,
+        include_archived: bool,
+    ) -> Result<Vec<(BOM, Vec<(Component, i32)>)>, DatabaseError>;
 
+    /// Deleted BOMs are not found.
     fn find_by_id(&self, bom_id: Uuid) -> Result<(BOM, Vec<(Component, i32)>), DatabaseError>;
 
+    fn find_deleted_by_id(&self, bom_id: Uuid) -> Result<BOM, DatabaseError>;
+
+    fn set_archived_at(
+        &self,
+        bom_id: Uuid,
+        archived_at: Option<DateTime<Utc>>,
+    ) -> Result<BOM, DatabaseError>;
+
+    fn set_deleted_at(
+        &self,
+        bom_id: Uuid,
+        deleted_at: Option<DateTime<Utc>>,
+    ) -> Result<BOM, DatabaseError>;
+
+    /// Permanently removes a deleted BOM together with its history.
+    fn purge(&self, bom_id: Uuid) -> Result<(), DatabaseError>;
+
+    /// Permanently removes every BOM deleted before `deleted_before` and
+    /// returns their ids.
+    fn purge_deleted_before(
+        &self,
+        deleted_before: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, DatabaseError>;
+
     fn insert(
         &self,
         new_bom: &BOM,
         new_bom_components: &[BomComponent],
         new_bom_version: &BomVersion,
+        lineage: Option<&BomLineage>,
     ) -> Result<(BOM, Vec<(Component, i32)>), DatabaseError>;
 
+    /// Fails with `DatabaseError::StaleVersion` when the stored BOM is no
+    /// longer at `base_version` and with `DatabaseError::Archived` when it was
+    /// archived in the meantime.
+    #[allow(clippy::too_many_arguments)]
     fn update_and_archive(
         &self,
         bom_id: Uuid,
+        base_version: i32,
         updated_bom: &BOM,
         updated_bom_components: &[BomComponent],
         updated_bom_version: &BomVersion,
+        new_bom_snapshot: Option<&BomSnapshot>,
     ) -> Result<(BOM, Vec<(Component, i32)>), DatabaseError>;
 
     fn get_bom_versions_until_version(
@@ -34,10 +73,139 @@ pub trait Repository: Send + Sync + 'static {
         version: i32,
     ) -> Result<Vec<BomVersion>, DatabaseError>;
 
+    fn get_bom_versions_page(
+        &self,
+        bom_id: Uuid,
+        offset: i64,
+        limit: i64,
+    ) -> Result<(Vec<BomVersion>, i64), DatabaseError>;
+
+    /// Returns the version in effect at `version`, i.e. the latest one that is
+    /// not newer than it.
+    fn find_bom_version(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+    ) -> Result<Option<BomVersion>, DatabaseError>;
+
+    /// Returns the latest version created at or before `instant`.
+    fn find_bom_version_as_of(
+        &self,
+        bom_id: Uuid,
+        instant: DateTime<Utc>,
+    ) -> Result<Option<BomVersion>, DatabaseError>;
+
+    fn get_bom_versions_after_version(
+        &self,
+        bom_id: Uuid,
+        after_version: i32,
+        until_version: i32,
+    ) -> Result<Vec<BomVersion>, DatabaseError>;
+
+    fn find_latest_snapshot_until_version(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+    ) -> Result<Option<BomSnapshot>, DatabaseError>;
+
+    fn find_snapshots(&self, bom_id: Uuid) -> Result<Vec<BomSnapshot>, DatabaseError>;
+
+    fn find_lineage(&self, bom_id: Uuid) -> Result<Option<BomLineage>, DatabaseError>;
+
+    /// Returns the lineage of every BOM cloned from one of `source_bom_ids`.
+    fn find_clones_of(&self, source_bom_ids: &[Uuid]) -> Result<Vec<BomLineage>, DatabaseError>;
+
+    fn find_tagged_bom_versions(&self, bom_id: Uuid) -> Result<Vec<BomVersion>, DatabaseError>;
+
+    fn tag_bom_version(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+        tag: &str,
+    ) -> Result<BomVersion, DatabaseError>;
+
+    /// Locks the BOM and hands its current version and its versions up to
+    /// `to` to `squash`. The versions from `from` up to the one `squash`
+    /// returns are then replaced by it, and the snapshots taken in between
+    /// are dropped, in the same transaction.
+    fn squash_bom_versions(
+        &self,
+        bom_id: Uuid,
+        from: i32,
+        to: i32,
+        squash: &mut dyn FnMut(i32, Vec<BomVersion>) -> Result<BomVersion, DatabaseError>,
+    ) -> Result<BomVersion, DatabaseError>;
+
+    /// Returns up to `limit` versions whose changes were stored with a schema
+    /// older than `schema_version`.
+    fn find_bom_versions_below_schema(
+        &self,
+        schema_version: i32,
+        limit: i64,
+    ) -> Result<Vec<BomVersion>, DatabaseError>;
+
+    /// Replaces the changes of the given versions, by id, with payloads
+    /// written in `schema_version`.
+    fn rewrite_bom_version_changes(
+        &self,
+        changes: &[(Uuid, Value)],
+        schema_version: i32,
+    ) -> Result<(), DatabaseError>;
+
+    /// Returns up to `limit` snapshots stored with a schema older than
+    /// `schema_version`.
+    fn find_bom_snapshots_below_schema(
+        &self,
+        schema_version: i32,
+        limit: i64,
+    ) -> Result<Vec<BomSnapshot>, DatabaseError>;
+
+    /// Replaces the given snapshots, by id, with payloads written in
+    /// `schema_version`.
+    fn rewrite_bom_snapshots(
+        &self,
+        snapshots: &[(Uuid, Value)],
+        schema_version: i32,
+    ) -> Result<(), DatabaseError>;
+
+    /// Stores `key` unless it is already taken, in which case the existing
+    /// record is returned. Records created before `expired_before` and
+    /// reservations without a response made before `abandoned_before` are
+    /// dropped first.
+    fn reserve_idempotency_key(
+        &self,
+        key: &IdempotencyKey,
+        expired_before: DateTime<Utc>,
+        abandoned_before: DateTime<Utc>,
+    ) -> Result<Option<IdempotencyKey>, DatabaseError>;
+
+    /// Stores the response of a reservation that has none yet.
+    fn complete_idempotency_key(&self, key: &str, response: &Value) -> Result<(), DatabaseError>;
+
+    /// Drops a reservation that has no response yet.
+    fn release_idempotency_key(&self, key: &str) -> Result<(), DatabaseError>;
+
     fn find_all_components(&self) -> Result<Vec<Component>, DatabaseError>;
 
     fn find_component_by_id(&self, component_id: Uuid) -> Result<Component, DatabaseError>;
 
+    fn upsert_component_lifecycle(
+        &self,
+        lifecycle: &ComponentLifecycle,
+    ) -> Result<ComponentLifecycle, DatabaseError>;
+
+    fn find_component_lifecycles(
+        &self,
+        component_ids: &[Uuid],
+    ) -> Result<Vec<ComponentLifecycle>, DatabaseError>;
+
+    /// Returns the components with one of `ids` or one of `part_numbers`.
+    fn find_components_by_ids_or_part_numbers(
+        &self,
+        ids: &[Uuid],
+        part_numbers: &[String],
+    ) -> Result<Vec<Component>, DatabaseError>;
+
     fn insert_component(&self, new_component: Component) -> Result<Component, DatabaseError>;
 
     fn update_component(&self, component: Component) -> Result<Component, DatabaseError>;
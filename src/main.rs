@@ -29,13 +29,13 @@ async fn main() -> Result<(), std::io::Error> {
 
     let repo = BomRepository::new(pool.clone());
 
-    let bom_service = Arc::new(BomService::new(Arc::new(repo)));
+    let bom_service = Arc::new(BomService::new(Arc::new(repo), config.bom.clone()));
 
     let addr = format!("{}:{}", config.app.host, config.app.port);
     println!("Server is running on: http://{}", addr);
     let listener = TcpListener::bind(addr).expect("Failed to bind to port");
 
-    run(listener, bom_service)?.await?;
+    run(listener, bom_service, config.app.admin_token)?.await?;
 
     Ok(())
 }
@@ -0,0 +1,129 @@
+use std::future::{ready, Ready};
+
+use actix_web::{
+    delete, dev::Payload, http::header::AUTHORIZATION, post, web, FromRequest, HttpRequest,
+    HttpResponse,
+};
+use secrecy::{ExposeSecret, Secret};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    domain::upcasting::CURRENT_EVENT_SCHEMA_VERSION,
+    services::{bom_service::BomService, error::ServiceError},
+};
+
+use super::ApiError;
+
+/// The token that grants access to the `/admin` routes, if any.
+pub struct AdminToken(pub Option<Secret<String>>);
+
+/// Proof that a request carries the admin token as its bearer token. Every
+/// admin route extracts it.
+pub struct Admin;
+
+impl FromRequest for Admin {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(authorize_admin(req))
+    }
+}
+
+fn authorize_admin(req: &HttpRequest) -> Result<Admin, ApiError> {
+    let expected = req
+        .app_data::<web::Data<AdminToken>>()
+        .and_then(|token| token.0.as_ref())
+        .ok_or_else(|| ApiError::Unauthorized("Admin routes are disabled".to_string()))?;
+
+    let given = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    // Digests are compared so that the time taken does not depend on how much
+    // of the token matches
+    match given {
+        Some(given)
+            if Sha256::digest(given.as_bytes())
+                == Sha256::digest(expected.expose_secret().as_bytes()) =>
+        {
+            Ok(Admin)
+        }
+        _ => Err(ApiError::Unauthorized(
+            "A valid admin token is required".to_string(),
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SquashRange {
+    from: i32,
+    to: i32,
+}
+
+#[tracing::instrument(name = "Squashing BOM versions", skip(_admin, bom_service, id, range), fields(request_id = %Uuid::new_v4()))]
+#[post("/admin/boms/{id}/squash")]
+pub async fn squash_bom_versions(
+    _admin: Admin,
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    range: web::Json<SquashRange>,
+) -> Result<HttpResponse, ApiError> {
+    let bom_id = id.into_inner();
+    let range = range.into_inner();
+
+    let squashed = actix_web::web::block(move || {
+        bom_service.squash_bom_versions(bom_id, range.from, range.to)
+    })
+    .await??;
+
+    Ok(HttpResponse::Ok().json(squashed))
+}
+
+#[tracing::instrument(name = "Purging BOM", skip(_admin, bom_service, id), fields(request_id = %Uuid::new_v4()))]
+#[delete("/admin/boms/{id}")]
+pub async fn purge_bom(
+    _admin: Admin,
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    actix_web::web::block(move || bom_service.purge_bom(id.into_inner())).await??;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(name = "Purging expired BOMs", skip(_admin, bom_service), fields(request_id = %Uuid::new_v4()))]
+#[post("/admin/boms/purge")]
+pub async fn purge_expired_boms(
+    _admin: Admin,
+    bom_service: web::Data<BomService>,
+) -> Result<HttpResponse, ApiError> {
+    let purged = actix_web::web::block(move || bom_service.purge_expired_boms()).await??;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "purged": purged })))
+}
+
+#[tracing::instrument(name = "Upgrading event schema", skip(_admin, bom_service), fields(request_id = %Uuid::new_v4()))]
+#[post("/admin/events/upgrade")]
+pub async fn upgrade_event_schema(
+    _admin: Admin,
+    bom_service: web::Data<BomService>,
+) -> Result<HttpResponse, ApiError> {
+    let (upgraded, snapshots_upgraded) = actix_web::web::block(move || {
+        Ok::<_, ServiceError>((
+            bom_service.upgrade_event_schema()?,
+            bom_service.upgrade_snapshot_schema()?,
+        ))
+    })
+    .await??;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "schema_version": CURRENT_EVENT_SCHEMA_VERSION,
+        "upgraded": upgraded,
+        "snapshots_upgraded": snapshots_upgraded,
+    })))
+}
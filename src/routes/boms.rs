@@ -1,64 +1,191 @@
-use actix_web::{get, post, put, web, HttpResponse};
-use serde::Deserialize;
+use actix_web::{
+    delete, get, http::header::CONTENT_TYPE, patch, post, put, web, HttpRequest, HttpResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    domain::{newtypes::new_bom::NewBOM, BOMChangeEvent, BOM},
-    services::bom_service::{BomService, UpdateOperation},
+    domain::{
+        newtypes::{
+            apply_diff::ApplyDiff, bom_patch::BOMPatch, bom_update::BOMUpdate,
+            cherry_pick::CherryPick, idempotency_key::IdempotencyKey, new_bom::NewBOM,
+        },
+        VersionMetadata, VersionedBOM, BOM,
+    },
+    services::{
+        bom_service::{BomService, UpdateOperation},
+        error::ServiceError,
+    },
 };
 
-use super::ApiError;
+use super::{expected_version, version_etag, ApiError};
+
+#[derive(Deserialize)]
+pub struct BOMListing {
+    #[serde(default)]
+    include_archived: bool,
+}
 
-#[tracing::instrument(name = "Getting BOMs", skip(bom_service), fields(request_id = %Uuid::new_v4()))]
+#[tracing::instrument(name = "Getting BOMs", skip(bom_service, listing), fields(request_id = %Uuid::new_v4()))]
 #[get("/boms")]
-pub async fn get_all_boms(bom_service: web::Data<BomService>) -> Result<HttpResponse, ApiError> {
-    let boms: Vec<BOM> = actix_web::web::block(move || bom_service.find_all_boms()).await??;
+pub async fn get_all_boms(
+    bom_service: web::Data<BomService>,
+    listing: web::Query<BOMListing>,
+) -> Result<HttpResponse, ApiError> {
+    let include_archived = listing.into_inner().include_archived;
+    let boms: Vec<BOM> =
+        actix_web::web::block(move || bom_service.find_all_boms(include_archived)).await??;
     Ok(HttpResponse::Ok().json(boms))
 }
 
-#[tracing::instrument(name = "Getting BOM by ID", skip(bom_service, id), fields(request_id = %Uuid::new_v4()))]
+#[derive(Deserialize)]
+pub struct AsOfQuery {
+    as_of: Option<DateTime<Utc>>,
+}
+
+#[tracing::instrument(name = "Getting BOM by ID", skip(bom_service, id, query), fields(request_id = %Uuid::new_v4()))]
 #[get("/boms/{id}")]
 pub async fn get_bom_by_id(
     bom_service: web::Data<BomService>,
     id: web::Path<Uuid>,
+    query: web::Query<AsOfQuery>,
 ) -> Result<HttpResponse, ApiError> {
-    let bom: BOM =
-        actix_web::web::block(move || bom_service.find_bom_by_id(id.into_inner())).await??;
-    Ok(HttpResponse::Ok().json(bom))
+    let bom_id = id.into_inner();
+
+    match query.into_inner().as_of {
+        Some(instant) => {
+            let bom: VersionedBOM =
+                actix_web::web::block(move || bom_service.find_bom_as_of(bom_id, instant))
+                    .await??;
+            Ok(HttpResponse::Ok().json(bom))
+        }
+        None => {
+            let bom: BOM =
+                actix_web::web::block(move || bom_service.find_bom_by_id(bom_id)).await??;
+            Ok(HttpResponse::Ok()
+                .insert_header(version_etag(bom.version))
+                .json(bom))
+        }
+    }
 }
 
-#[tracing::instrument(name = "Creating BOM", skip(bom_service), fields(request_id = %Uuid::new_v4(), new_bom = %new_bom))]
+#[tracing::instrument(name = "Creating BOM", skip(req, bom_service), fields(request_id = %Uuid::new_v4(), new_bom = %new_bom))]
 #[post("/boms")]
 pub async fn create_bom(
+    req: HttpRequest,
     bom_service: web::Data<BomService>,
     new_bom: web::Json<NewBOM>,
 ) -> Result<HttpResponse, ApiError> {
     let new_bom = new_bom.into_inner();
-    let new_bom: BOM = actix_web::web::block(move || bom_service.insert_bom(new_bom)).await??;
+    let key = idempotency_key(&req, "POST /boms", &new_bom)?;
 
-    Ok(HttpResponse::Created().json(new_bom))
+    let created = actix_web::web::block(move || {
+        bom_service.write_idempotently(key, || bom_service.insert_bom(new_bom))
+    })
+    .await??;
+
+    Ok(HttpResponse::Created()
+        .insert_header(version_etag(created.bom.bom.version))
+        .insert_header(("Idempotent-Replayed", created.replayed.to_string()))
+        .json(created.bom))
 }
 
-#[tracing::instrument(name = "Updating BOM", skip(bom_service), fields(request_id = %Uuid::new_v4(), id = %id))]
+#[tracing::instrument(name = "Updating BOM", skip(req, bom_service), fields(request_id = %Uuid::new_v4(), id = %id))]
 #[put("/boms/{id}")]
 pub async fn update_bom(
+    req: HttpRequest,
     bom_service: web::Data<BomService>,
     id: web::Path<Uuid>,
-    change_events: web::Json<Vec<BOMChangeEvent>>,
+    update: web::Json<BOMUpdate>,
 ) -> Result<HttpResponse, ApiError> {
-    let change_events = change_events.into_inner();
+    let update = with_if_match(&req, update.into_inner())?;
     let bom_id = id.into_inner();
 
-    let updated_bom: BOM = actix_web::web::block(move || {
-        bom_service.update_bom(
-            bom_id,
-            Box::new(change_events),
-            UpdateOperation::Incremental,
-        )
+    let key = idempotency_key(&req, &format!("PUT /boms/{}", bom_id), &update)?;
+
+    let updated = actix_web::web::block(move || {
+        bom_service.write_idempotently(key, || {
+            bom_service.update_bom(bom_id, update, UpdateOperation::Incremental)
+        })
     })
     .await??;
 
-    Ok(HttpResponse::Created().json(updated_bom))
+    Ok(HttpResponse::Created()
+        .insert_header(version_etag(updated.bom.bom.version))
+        .insert_header(("Idempotent-Replayed", updated.replayed.to_string()))
+        .json(updated.bom))
+}
+
+/// Takes the expected version of `update` from the `If-Match` header, if any.
+fn with_if_match(req: &HttpRequest, update: BOMUpdate) -> Result<BOMUpdate, ApiError> {
+    let Some(version) = expected_version(req)? else {
+        return Ok(update);
+    };
+
+    if update
+        .expected_version
+        .is_some_and(|expected| expected != version)
+    {
+        return Err(ApiError::BadRequest(
+            "If-Match and expected_version disagree".to_string(),
+        ));
+    }
+
+    Ok(update.expecting_version(version))
+}
+
+#[tracing::instrument(name = "Previewing BOM update", skip(req, bom_service, update), fields(request_id = %Uuid::new_v4(), id = %id))]
+#[post("/boms/{id}/preview")]
+pub async fn preview_bom_update(
+    req: HttpRequest,
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    update: web::Json<BOMUpdate>,
+) -> Result<HttpResponse, ApiError> {
+    let update = with_if_match(&req, update.into_inner())?;
+    let bom_id = id.into_inner();
+
+    let preview =
+        actix_web::web::block(move || bom_service.preview_update(bom_id, update)).await??;
+
+    Ok(HttpResponse::Ok().json(preview))
+}
+
+#[tracing::instrument(name = "Patching BOM", skip(req, bom_service, metadata, body), fields(request_id = %Uuid::new_v4(), id = %id))]
+#[patch("/boms/{id}")]
+pub async fn patch_bom(
+    req: HttpRequest,
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    metadata: web::Query<VersionMetadata>,
+    body: web::Bytes,
+) -> Result<HttpResponse, ApiError> {
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if content_type.split(';').next().map(str::trim) != Some("application/json-patch+json") {
+        return Err(ApiError::UnsupportedMediaType(
+            "PATCH expects application/json-patch+json".to_string(),
+        ));
+    }
+
+    let patch: BOMPatch = serde_json::from_slice(&body)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid JSON Patch: {}", e)))?;
+    let expected_version = expected_version(&req)?;
+    let bom_id = id.into_inner();
+    let metadata = metadata.into_inner();
+
+    let patched = actix_web::web::block(move || {
+        bom_service.patch_bom(bom_id, patch, expected_version, metadata)
+    })
+    .await??;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(version_etag(patched.bom.version))
+        .json(patched))
 }
 
 #[derive(Deserialize)]
@@ -108,6 +235,9 @@ pub async fn get_bom_version(
 #[derive(Deserialize)]
 struct RevertBOM {
     revert_to_version: i32,
+    author: Option<String>,
+    message: Option<String>,
+    ticket: Option<String>,
 }
 
 #[put("/boms/{id}/")]
@@ -119,10 +249,275 @@ pub async fn revert_bom_to_version(
     let bom_id = id.into_inner();
     let version = version.into_inner();
 
+    let metadata = VersionMetadata {
+        author: version.author,
+        message: version.message,
+        ticket: version.ticket,
+    };
+
     let reverted_bom = actix_web::web::block(move || {
-        bom_service.revert_bom_to_version(bom_id, version.revert_to_version)
+        bom_service.revert_bom_to_version(bom_id, version.revert_to_version, metadata)
+    })
+    .await??;
+
+    Ok(HttpResponse::Created()
+        .insert_header(version_etag(reverted_bom.bom.version))
+        .json(reverted_bom))
+}
+
+#[tracing::instrument(name = "Checking BOM snapshot consistency", skip(bom_service, id), fields(request_id = %Uuid::new_v4()))]
+#[get("/boms/{id}/snapshots/consistency")]
+pub async fn check_snapshot_consistency(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let report =
+        actix_web::web::block(move || bom_service.check_snapshot_consistency(id.into_inner()))
+            .await??;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[derive(Deserialize)]
+pub struct Pagination {
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_per_page")]
+    per_page: i64,
+}
+
+fn default_page() -> i64 {
+    1
+}
+
+fn default_per_page() -> i64 {
+    20
+}
+
+#[tracing::instrument(name = "Getting BOM version history", skip(bom_service, id, pagination), fields(request_id = %Uuid::new_v4()))]
+#[get("/boms/{id}/versions")]
+pub async fn get_bom_versions(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    pagination: web::Query<Pagination>,
+) -> Result<HttpResponse, ApiError> {
+    let bom_id = id.into_inner();
+    let pagination = pagination.into_inner();
+
+    let history = actix_web::web::block(move || {
+        bom_service.get_version_history(bom_id, pagination.page, pagination.per_page)
     })
     .await??;
 
-    Ok(HttpResponse::Created().json(reverted_bom))
+    Ok(HttpResponse::Ok().json(history))
+}
+
+#[derive(Deserialize)]
+pub struct BlameQuery {
+    version: Option<i32>,
+}
+
+#[tracing::instrument(name = "Getting BOM blame", skip(bom_service, id, query), fields(request_id = %Uuid::new_v4()))]
+#[get("/boms/{id}/blame")]
+pub async fn get_bom_blame(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    query: web::Query<BlameQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let bom_id = id.into_inner();
+    let version = query.into_inner().version;
+
+    let blame = actix_web::web::block(move || bom_service.blame_bom(bom_id, version)).await??;
+
+    Ok(HttpResponse::Ok().json(blame))
+}
+
+#[derive(Deserialize)]
+pub struct LintQuery {
+    version: Option<i32>,
+}
+
+#[tracing::instrument(name = "Linting BOM", skip(bom_service, id, query), fields(request_id = %Uuid::new_v4()))]
+#[get("/boms/{id}/lint")]
+pub async fn lint_bom(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    query: web::Query<LintQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let bom_id = id.into_inner();
+    let version = query.into_inner().version;
+
+    let lint = actix_web::web::block(move || bom_service.lint_bom(bom_id, version)).await??;
+
+    Ok(HttpResponse::Ok().json(lint))
+}
+
+#[tracing::instrument(name = "Applying diff to BOM", skip(req, bom_service, apply_diff), fields(request_id = %Uuid::new_v4(), id = %id))]
+#[post("/boms/{id}/apply-diff")]
+pub async fn apply_diff(
+    req: HttpRequest,
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    apply_diff: web::Json<ApplyDiff>,
+) -> Result<HttpResponse, ApiError> {
+    let bom_id = id.into_inner();
+    let apply_diff = apply_diff.into_inner();
+    let expected_version = expected_version(&req)?;
+
+    let updated_bom: VersionedBOM =
+        actix_web::web::block(move || bom_service.apply_diff(bom_id, apply_diff, expected_version))
+            .await??;
+
+    Ok(HttpResponse::Created()
+        .insert_header(version_etag(updated_bom.bom.version))
+        .json(updated_bom))
+}
+
+#[tracing::instrument(name = "Cherry-picking into BOM", skip(bom_service, id, cherry_pick), fields(request_id = %Uuid::new_v4()))]
+#[post("/boms/{id}/cherry-pick")]
+pub async fn cherry_pick(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    cherry_pick: web::Json<CherryPick>,
+) -> Result<HttpResponse, ApiError> {
+    let bom_id = id.into_inner();
+    let cherry_pick = cherry_pick.into_inner();
+
+    let updated_bom: VersionedBOM =
+        actix_web::web::block(move || bom_service.cherry_pick(bom_id, cherry_pick)).await??;
+
+    Ok(HttpResponse::Created()
+        .insert_header(version_etag(updated_bom.bom.version))
+        .json(updated_bom))
+}
+
+#[derive(Deserialize)]
+pub struct CloneBOM {
+    version: Option<i32>,
+    name: Option<String>,
+    author: Option<String>,
+    message: Option<String>,
+    ticket: Option<String>,
+}
+
+#[tracing::instrument(name = "Cloning BOM", skip(bom_service, id, query), fields(request_id = %Uuid::new_v4()))]
+#[post("/boms/{id}/clone")]
+pub async fn clone_bom(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    query: web::Query<CloneBOM>,
+) -> Result<HttpResponse, ApiError> {
+    let bom_id = id.into_inner();
+    let query = query.into_inner();
+
+    let metadata = VersionMetadata {
+        author: query.author,
+        message: query.message,
+        ticket: query.ticket,
+    };
+
+    let cloned: VersionedBOM = actix_web::web::block(move || {
+        bom_service.clone_bom(bom_id, query.version, query.name, metadata)
+    })
+    .await??;
+
+    Ok(HttpResponse::Created()
+        .insert_header(version_etag(cloned.bom.version))
+        .json(cloned))
+}
+
+#[tracing::instrument(name = "Getting BOM lineage", skip(bom_service, id), fields(request_id = %Uuid::new_v4()))]
+#[get("/boms/{id}/lineage")]
+pub async fn get_bom_lineage(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let lineage = actix_web::web::block(move || bom_service.get_lineage(id.into_inner())).await??;
+
+    Ok(HttpResponse::Ok().json(lineage))
+}
+
+#[tracing::instrument(name = "Archiving BOM", skip(bom_service, id), fields(request_id = %Uuid::new_v4()))]
+#[post("/boms/{id}/archive")]
+pub async fn archive_bom(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let bom = actix_web::web::block(move || bom_service.archive_bom(id.into_inner())).await??;
+
+    Ok(HttpResponse::Ok().json(bom))
+}
+
+#[tracing::instrument(name = "Unarchiving BOM", skip(bom_service, id), fields(request_id = %Uuid::new_v4()))]
+#[post("/boms/{id}/unarchive")]
+pub async fn unarchive_bom(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let bom = actix_web::web::block(move || bom_service.unarchive_bom(id.into_inner())).await??;
+
+    Ok(HttpResponse::Ok().json(bom))
+}
+
+#[tracing::instrument(name = "Deleting BOM", skip(bom_service, id), fields(request_id = %Uuid::new_v4()))]
+#[delete("/boms/{id}")]
+pub async fn delete_bom(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    actix_web::web::block(move || bom_service.delete_bom(id.into_inner())).await??;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(name = "Restoring BOM", skip(bom_service, id), fields(request_id = %Uuid::new_v4()))]
+#[post("/boms/{id}/restore")]
+pub async fn restore_bom(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let bom = actix_web::web::block(move || bom_service.restore_bom(id.into_inner())).await??;
+
+    Ok(HttpResponse::Ok().json(bom))
+}
+
+#[derive(Deserialize)]
+pub struct VersionTag {
+    tag: String,
+}
+
+#[tracing::instrument(name = "Tagging BOM version", skip(bom_service, path, body), fields(request_id = %Uuid::new_v4()))]
+#[put("/boms/{id}/versions/{version}/tag")]
+pub async fn tag_bom_version(
+    bom_service: web::Data<BomService>,
+    path: web::Path<(Uuid, i32)>,
+    body: web::Json<VersionTag>,
+) -> Result<HttpResponse, ApiError> {
+    let (bom_id, version) = path.into_inner();
+    let tag = body.into_inner().tag;
+
+    let tagged =
+        actix_web::web::block(move || bom_service.tag_bom_version(bom_id, version, &tag)).await??;
+
+    Ok(HttpResponse::Ok().json(tagged))
+}
+
+/// Reads the optional `Idempotency-Key` header and fingerprints the request
+/// it came with.
+fn idempotency_key(
+    req: &HttpRequest,
+    scope: &str,
+    payload: &impl Serialize,
+) -> Result<Option<IdempotencyKey>, ApiError> {
+    let Some(key) = req.headers().get("Idempotency-Key") else {
+        return Ok(None);
+    };
+
+    let key = key
+        .to_str()
+        .map_err(|_| ApiError::BadRequest("Idempotency-Key must be plain text".to_string()))?;
+
+    IdempotencyKey::new(key, scope, payload)
+        .map(Some)
+        .map_err(|error| ServiceError::from(error).into())
 }
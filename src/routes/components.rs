@@ -1,9 +1,9 @@
-use actix_web::{get, post, web, HttpResponse};
+use actix_web::{get, post, put, web, HttpResponse};
 use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
-    domain::{newtypes::new_component::NewComponent, Component},
+    domain::{newtypes::new_component::NewComponent, Component, LifecycleStatus},
     services::bom_service::BomService,
 };
 
@@ -59,3 +59,23 @@ pub async fn create_component(
             .await??;
     Ok(HttpResponse::Created().json(new_component))
 }
+
+#[derive(Deserialize)]
+pub struct LifecycleUpdate {
+    status: LifecycleStatus,
+}
+
+#[tracing::instrument(name = "Setting the lifecycle of a component", skip(bom_service, update), fields(request_id = %Uuid::new_v4(), id = %id))]
+#[put("/components/{id}/lifecycle")]
+pub async fn set_component_lifecycle(
+    bom_service: web::Data<BomService>,
+    id: web::Path<Uuid>,
+    update: web::Json<LifecycleUpdate>,
+) -> Result<HttpResponse, ApiError> {
+    let lifecycle = actix_web::web::block(move || {
+        bom_service.set_component_lifecycle(id.into_inner(), update.into_inner().status)
+    })
+    .await??;
+
+    Ok(HttpResponse::Ok().json(lifecycle))
+}
@@ -0,0 +1,34 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    domain::{newtypes::bom_reference::BOMReference, ComponentMatching},
+    services::bom_service::BomService,
+};
+
+use super::ApiError;
+
+#[derive(Debug, Deserialize)]
+pub struct BOMComparison {
+    pub left: BOMReference,
+    pub right: BOMReference,
+    #[serde(default)]
+    pub match_by: ComponentMatching,
+}
+
+#[tracing::instrument(name = "Comparing BOMs", skip(bom_service), fields(request_id = %Uuid::new_v4(), left = %params.left, right = %params.right))]
+#[get("/diffs")]
+pub async fn compare_boms(
+    bom_service: web::Data<BomService>,
+    params: web::Query<BOMComparison>,
+) -> Result<HttpResponse, ApiError> {
+    let params = params.into_inner();
+
+    let diff = actix_web::web::block(move || {
+        bom_service.compare_boms(params.left, params.right, params.match_by)
+    })
+    .await??;
+
+    Ok(HttpResponse::Ok().json(diff))
+}
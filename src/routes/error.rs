@@ -2,17 +2,38 @@ use actix_web::{error::BlockingError, ResponseError};
 use diesel::result::Error as DieselError;
 
 use crate::{
-    domain::error::DomainError, infrastructure::error::DatabaseError, services::error::ServiceError,
+    domain::{error::DomainError, rules::RuleViolation, validation::RejectedEvent, DiffConflict},
+    infrastructure::error::DatabaseError,
+    services::error::ServiceError,
 };
 
+use super::etag::version_etag;
+
 #[derive(thiserror::Error, Debug)]
 pub enum ApiError {
     #[error("Internal Server Error: {0}")]
     Unexpected(String),
     #[error("Bad Request: {0}")]
     BadRequest(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
     #[error("Not Found: {0}")]
     NotFound(String),
+    #[error("Conflict: {message}")]
+    Conflict {
+        message: String,
+        current_version: Option<i32>,
+    },
+    #[error("Conflict: {} diff entry(s) do not match the BOM", .0.len())]
+    DiffConflicts(Vec<DiffConflict>),
+    #[error("Unsupported Media Type: {0}")]
+    UnsupportedMediaType(String),
+    #[error("Unprocessable Entity: {0}")]
+    UnprocessableEntity(String),
+    #[error("Unprocessable Entity: {} event(s) cannot be applied", .0.len())]
+    UnprocessableEvents(Vec<RejectedEvent>),
+    #[error("Unprocessable Entity: {} rule(s) violated", .0.len())]
+    RuleViolations(Vec<RuleViolation>),
 }
 
 impl From<BlockingError> for ApiError {
@@ -26,13 +47,52 @@ impl ResponseError for ApiError {
         match self {
             ApiError::Unexpected(_) => actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::BadRequest(_) => actix_web::http::StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => actix_web::http::StatusCode::UNAUTHORIZED,
             ApiError::NotFound(_) => actix_web::http::StatusCode::NOT_FOUND,
+            ApiError::Conflict { .. } | ApiError::DiffConflicts(_) => {
+                actix_web::http::StatusCode::CONFLICT
+            }
+            ApiError::UnsupportedMediaType(_) => {
+                actix_web::http::StatusCode::UNSUPPORTED_MEDIA_TYPE
+            }
+            ApiError::UnprocessableEntity(_)
+            | ApiError::UnprocessableEvents(_)
+            | ApiError::RuleViolations(_) => actix_web::http::StatusCode::UNPROCESSABLE_ENTITY,
         }
     }
 
     fn error_response(&self) -> actix_web::HttpResponse<actix_web::body::BoxBody> {
-        actix_web::HttpResponse::build(self.status_code())
-            .json(serde_json::json!({ "error": self.to_string() }))
+        match self {
+            ApiError::Conflict {
+                current_version: Some(current_version),
+                ..
+            } => actix_web::HttpResponse::build(self.status_code())
+                .insert_header(version_etag(*current_version))
+                .json(serde_json::json!({
+                    "error": self.to_string(),
+                    "current_version": current_version,
+                })),
+            ApiError::UnprocessableEvents(rejected_events) => {
+                actix_web::HttpResponse::build(self.status_code()).json(serde_json::json!({
+                    "error": self.to_string(),
+                    "rejected_events": rejected_events,
+                }))
+            }
+            ApiError::DiffConflicts(conflicts) => {
+                actix_web::HttpResponse::build(self.status_code()).json(serde_json::json!({
+                    "error": self.to_string(),
+                    "conflicts": conflicts,
+                }))
+            }
+            ApiError::RuleViolations(violations) => {
+                actix_web::HttpResponse::build(self.status_code()).json(serde_json::json!({
+                    "error": self.to_string(),
+                    "violations": violations,
+                }))
+            }
+            _ => actix_web::HttpResponse::build(self.status_code())
+                .json(serde_json::json!({ "error": self.to_string() })),
+        }
     }
 }
 
@@ -49,8 +109,40 @@ impl From<ServiceError> for ApiError {
                     _ => Self::Unexpected(error.to_string()),
                 },
                 DatabaseError::R2D2Error(error) => Self::Unexpected(error.to_string()),
+                DatabaseError::StaleVersion(current_version) => Self::Conflict {
+                    message: "The BOM was changed concurrently, please retry".to_string(),
+                    current_version: Some(current_version),
+                },
+                DatabaseError::Archived(bom_id) => Self::Conflict {
+                    message: format!("BOM {} is archived and cannot be changed", bom_id),
+                    current_version: None,
+                },
             },
             ServiceError::InvalidData(message) => Self::BadRequest(message),
+            ServiceError::VersionConflict { expected, current } => Self::Conflict {
+                message: format!(
+                    "Expected version {} but the BOM is at version {}",
+                    expected, current
+                ),
+                current_version: Some(current),
+            },
+            ServiceError::IdempotencyKeyReused(message) => Self::UnprocessableEntity(message),
+            ServiceError::EventsRejected(rejected_events) => {
+                Self::UnprocessableEvents(rejected_events)
+            }
+            ServiceError::InvalidState(message) => Self::Conflict {
+                message,
+                current_version: None,
+            },
+            ServiceError::RequestInProgress(message) => Self::Conflict {
+                message,
+                current_version: None,
+            },
+            ServiceError::UnresolvedComponents(reasons) => {
+                Self::UnprocessableEntity(reasons.join("; "))
+            }
+            ServiceError::RulesViolated(violations) => Self::RuleViolations(violations),
+            ServiceError::DiffConflicts(conflicts) => Self::DiffConflicts(conflicts),
         }
     }
 }
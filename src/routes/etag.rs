@@ -0,0 +1,26 @@
+use actix_web::{
+    http::header::{ETag, EntityTag, Header, IfMatch},
+    HttpRequest,
+};
+
+use super::ApiError;
+
+/// The entity tag of a BOM at `version`.
+pub fn version_etag(version: i32) -> ETag {
+    ETag(EntityTag::new_strong(version.to_string()))
+}
+
+/// Reads the version a client expects from the `If-Match` header. A missing
+/// header or `*` means any version is fine.
+pub fn expected_version(req: &HttpRequest) -> Result<Option<i32>, ApiError> {
+    let invalid = || ApiError::BadRequest("If-Match must hold a single BOM version".to_string());
+
+    match IfMatch::parse(req).map_err(|_| invalid())? {
+        IfMatch::Any => Ok(None),
+        IfMatch::Items(tags) => match tags.as_slice() {
+            [] => Ok(None),
+            [tag] => tag.tag().parse::<i32>().map(Some).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        },
+    }
+}
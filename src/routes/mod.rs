@@ -1,9 +1,15 @@
+pub mod admin;
 pub mod boms;
 pub mod components;
+pub mod diffs;
 pub mod error;
+pub mod etag;
 pub mod health_check;
 
+pub use admin::*;
 pub use boms::*;
 pub use components::*;
+pub use diffs::*;
 pub use error::*;
+pub use etag::*;
 pub use health_check::*;
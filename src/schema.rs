@@ -1,5 +1,25 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    bom_lineage (bom_id) {
+        bom_id -> Uuid,
+        source_bom_id -> Uuid,
+        source_version -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    bom_snapshots (id) {
+        id -> Uuid,
+        bom_id -> Uuid,
+        version -> Int4,
+        snapshot -> Jsonb,
+        created_at -> Timestamptz,
+        schema_version -> Int4,
+    }
+}
+
 diesel::table! {
     bom_versions (id) {
         id -> Uuid,
@@ -7,6 +27,13 @@ diesel::table! {
         version -> Int4,
         changes -> Jsonb,
         created_at -> Timestamptz,
+        author -> Nullable<Varchar>,
+        message -> Nullable<Text>,
+        ticket -> Nullable<Varchar>,
+        reverted_to -> Nullable<Int4>,
+        tag -> Nullable<Varchar>,
+        squashed_from -> Nullable<Int4>,
+        schema_version -> Int4,
     }
 }
 
@@ -18,6 +45,10 @@ diesel::table! {
         version -> Int4,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        archived_at -> Nullable<Timestamptz>,
+        deleted_at -> Nullable<Timestamptz>,
+        category -> Nullable<Varchar>,
+        rule_set -> Nullable<Varchar>,
     }
 }
 
@@ -26,6 +57,15 @@ diesel::table! {
         bom_id -> Uuid,
         component_id -> Uuid,
         quantity -> Int4,
+        position -> Int4,
+    }
+}
+
+diesel::table! {
+    component_lifecycles (component_id) {
+        component_id -> Uuid,
+        status -> Varchar,
+        updated_at -> Timestamptz,
     }
 }
 
@@ -41,8 +81,30 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    idempotency_keys (key) {
+        key -> Varchar,
+        request_hash -> Varchar,
+        response -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+        schema_version -> Int4,
+    }
+}
+
+diesel::joinable!(bom_lineage -> boms (bom_id));
+diesel::joinable!(bom_snapshots -> boms (bom_id));
 diesel::joinable!(bom_versions -> boms (bom_id));
 diesel::joinable!(boms_components -> boms (bom_id));
 diesel::joinable!(boms_components -> components (component_id));
+diesel::joinable!(component_lifecycles -> components (component_id));
 
-diesel::allow_tables_to_appear_in_same_query!(bom_versions, boms, boms_components, components,);
+diesel::allow_tables_to_appear_in_same_query!(
+    bom_lineage,
+    bom_snapshots,
+    bom_versions,
+    boms,
+    boms_components,
+    component_lifecycles,
+    components,
+    idempotency_keys,
+);
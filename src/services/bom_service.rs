@@ -1,17 +1,39 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
+use chrono::{DateTime, Duration, Utc};
 use uuid::Uuid;
 
 use crate::{
+    configuration::BomSettings,
     domain::{
-        newtypes::{new_bom::NewBOM, new_component::NewComponent},
-        validation::BOMChangeEventValidator,
-        BOMChangeEvent, BOMDiff, BomVersion, Component as DomainComponent, CountedComponent, BOM,
+        error::DomainError,
+        newtypes::{
+            apply_diff::ApplyDiff,
+            bom_patch::BOMPatch,
+            bom_reference::BOMReference,
+            bom_update::BOMUpdate,
+            change_request::{BOMChangeRequest, ComponentReference},
+            cherry_pick::CherryPick,
+            idempotency_key::IdempotencyKey,
+            new_bom::NewBOM,
+            new_component::NewComponent,
+        },
+        rules::check_rules,
+        upcasting::CURRENT_EVENT_SCHEMA_VERSION,
+        validation::{normalize_events, BOMChangeEventValidator},
+        BOMBlame, BOMChangeEvent, BOMDiff, BOMLineage, BOMLint, BOMPreview, BomSnapshot,
+        BomVersion, Component as DomainComponent, ComponentLifecycle, ComponentMatching,
+        CountedComponent, IdempotencyRecord, IdempotentWrite, LifecycleStatus, LineageLink,
+        SnapshotConsistency, VersionHistory, VersionMetadata, VersionSummary, VersionedBOM, BOM,
     },
     infrastructure::{
+        aliases::DieselError,
+        error::DatabaseError,
         models::{
             bom::BOM as DbBOM, bom_components::BomComponent,
+            bom_lineage::BomLineage as DbBomLineage, bom_snapshot::BomSnapshot as DbBomSnapshot,
             bom_version::BomVersion as DbBomVersion,
+            idempotency_key::IdempotencyKey as DbIdempotencyKey,
         },
         repositories::repository::Repository,
     },
@@ -19,26 +41,37 @@ use crate::{
 
 use super::error::ServiceError;
 
+/// How many times a write is attempted before giving up on concurrent
+/// writers.
+const MAX_WRITE_ATTEMPTS: u32 = 5;
+
+/// How many stored versions are rewritten per transaction when upgrading the
+/// event schema.
+const UPGRADE_BATCH_SIZE: i64 = 500;
+
+#[derive(Clone, Copy)]
 pub enum UpdateOperation {
     Incremental,
-    Revert,
+    /// Brings the BOM back to the state of the given version.
+    Revert(i32),
 }
 
 pub struct BomService {
     repo: Arc<dyn Repository>,
+    settings: BomSettings,
 }
 
 impl BomService {
-    pub fn new(repo: Arc<dyn Repository>) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<dyn Repository>, settings: BomSettings) -> Self {
+        Self { repo, settings }
     }
 }
 
 impl BomService {
-    pub fn find_all_boms(&self) -> Result<Vec<BOM>, ServiceError> {
+    pub fn find_all_boms(&self, include_archived: bool) -> Result<Vec<BOM>, ServiceError> {
         Ok(self
             .repo
-            .find_all()?
+            .find_all(include_archived)?
             .into_iter()
             .map(|(bom, components)| BOM::from((bom, components)))
             .collect())
@@ -48,125 +81,728 @@ impl BomService {
         Ok(self.repo.find_by_id(bom_id)?.into())
     }
 
-    pub fn find_bom_by_version_and_id(
-        &self,
-        bom_id: Uuid,
-        version: i32,
-    ) -> Result<BOM, ServiceError> {
-        let mut bom: BOM = self.repo.find_by_id(bom_id)?.into();
+    pub fn archive_bom(&self, bom_id: Uuid) -> Result<BOM, ServiceError> {
+        let bom = self.find_bom_by_id(bom_id)?;
+        if bom.archived_at.is_some() {
+            return Ok(bom);
+        }
 
-        if bom.version < version {
-            return Err(ServiceError::InvalidData(format!(
-                "Version not found. Your latest version is {}",
-                &bom.version
+        self.repo.set_archived_at(bom_id, Some(Utc::now()))?;
+        self.find_bom_by_id(bom_id)
+    }
+
+    pub fn unarchive_bom(&self, bom_id: Uuid) -> Result<BOM, ServiceError> {
+        self.find_bom_by_id(bom_id)?;
+        self.repo.set_archived_at(bom_id, None)?;
+        self.find_bom_by_id(bom_id)
+    }
+
+    /// Hides the BOM everywhere. It can be restored within the restore window.
+    pub fn delete_bom(&self, bom_id: Uuid) -> Result<(), ServiceError> {
+        self.repo.find_by_id(bom_id)?;
+        self.repo.set_deleted_at(bom_id, Some(Utc::now()))?;
+        Ok(())
+    }
+
+    pub fn restore_bom(&self, bom_id: Uuid) -> Result<BOM, ServiceError> {
+        let deleted = self.repo.find_deleted_by_id(bom_id)?;
+
+        if deleted.deleted_at < Some(self.restore_deadline()) {
+            return Err(ServiceError::InvalidState(format!(
+                "BOM {} was deleted more than {} days ago and can no longer be restored",
+                bom_id, self.settings.restore_window_days
             )));
         }
 
-        let versions = self.fetch_bom_versions_until_version(bom_id, version)?;
+        self.repo.set_deleted_at(bom_id, None)?;
+        self.find_bom_by_id(bom_id)
+    }
 
-        bom.clean_for_revert();
-        if let Some(version) = versions.last() {
-            bom.version = version.version + 1;
+    /// Permanently removes a deleted BOM and its whole history.
+    pub fn purge_bom(&self, bom_id: Uuid) -> Result<(), ServiceError> {
+        self.repo.find_deleted_by_id(bom_id)?;
+        Ok(self.repo.purge(bom_id)?)
+    }
+
+    /// Permanently removes every BOM whose restore window has passed.
+    pub fn purge_expired_boms(&self) -> Result<Vec<Uuid>, ServiceError> {
+        Ok(self.repo.purge_deleted_before(self.restore_deadline())?)
+    }
+
+    /// Rewrites every version stored with an older event schema to the
+    /// current one and returns how many were rewritten.
+    pub fn upgrade_event_schema(&self) -> Result<usize, ServiceError> {
+        let mut upgraded = 0;
+
+        loop {
+            let outdated = self
+                .repo
+                .find_bom_versions_below_schema(CURRENT_EVENT_SCHEMA_VERSION, UPGRADE_BATCH_SIZE)?;
+
+            if outdated.is_empty() {
+                return Ok(upgraded);
+            }
+
+            let rewritten = outdated
+                .into_iter()
+                .map(|version| {
+                    let id = version.id;
+                    let changes = serde_json::to_value(BomVersion::try_from(version)?.changes)
+                        .map_err(|e| DomainError::ConversionError(e.to_string()))?;
+                    Ok((id, changes))
+                })
+                .collect::<Result<Vec<_>, ServiceError>>()?;
+
+            self.repo
+                .rewrite_bom_version_changes(&rewritten, CURRENT_EVENT_SCHEMA_VERSION)?;
+            upgraded += rewritten.len();
         }
+    }
 
-        for version in versions.iter() {
-            for change_event in version.changes.iter() {
-                bom.apply_change(change_event, BOMChangeEventValidator)?;
+    /// Rewrites every snapshot stored with an older schema to the current one
+    /// and returns how many were rewritten.
+    pub fn upgrade_snapshot_schema(&self) -> Result<usize, ServiceError> {
+        let mut upgraded = 0;
+
+        loop {
+            let outdated = self.repo.find_bom_snapshots_below_schema(
+                CURRENT_EVENT_SCHEMA_VERSION,
+                UPGRADE_BATCH_SIZE,
+            )?;
+
+            if outdated.is_empty() {
+                return Ok(upgraded);
             }
+
+            let rewritten = outdated
+                .into_iter()
+                .map(|snapshot| {
+                    let id = snapshot.id;
+                    let bom = serde_json::to_value(BomSnapshot::try_from(snapshot)?.bom)
+                        .map_err(|e| DomainError::ConversionError(e.to_string()))?;
+                    Ok((id, bom))
+                })
+                .collect::<Result<Vec<_>, ServiceError>>()?;
+
+            self.repo
+                .rewrite_bom_snapshots(&rewritten, CURRENT_EVENT_SCHEMA_VERSION)?;
+            upgraded += rewritten.len();
         }
+    }
 
-        Ok(bom)
+    pub fn find_bom_by_version_and_id(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+    ) -> Result<VersionedBOM, ServiceError> {
+        let bom = self.reconstruct_bom(bom_id, version)?;
+
+        let metadata = match self.repo.find_bom_version(bom_id, version)? {
+            Some(version) => BomVersion::try_from(version)?.metadata,
+            None => VersionMetadata::default(),
+        };
+
+        Ok(VersionedBOM::new(bom, metadata))
     }
 
-    pub fn insert_bom(&self, new_bom: NewBOM) -> Result<BOM, ServiceError> {
-        let bom: BOM = BOM::try_from(&new_bom)?;
+    /// Reconstructs the BOM at the latest version created at or before
+    /// `instant`.
+    pub fn find_bom_as_of(
+        &self,
+        bom_id: Uuid,
+        instant: DateTime<Utc>,
+    ) -> Result<VersionedBOM, ServiceError> {
+        self.repo.find_by_id(bom_id)?;
 
-        let new_bom_components = self.transform_counted_components(&bom.id, &bom.components);
+        let version = self
+            .repo
+            .find_bom_version_as_of(bom_id, instant)?
+            .ok_or_else(|| {
+                ServiceError::InvalidData(format!(
+                    "BOM {} has no version at or before {}",
+                    bom_id,
+                    instant.to_rfc3339()
+                ))
+            })?;
 
-        let new_bom_version: DbBomVersion =
-            BomVersion::new(&bom.id, 0, Box::new(new_bom.events)).try_into()?;
+        self.find_bom_by_version_and_id(bom_id, version.version)
+    }
 
-        let (bom, components) =
-            self.repo
-                .insert(&bom.into(), &new_bom_components, &new_bom_version)?;
+    pub fn insert_bom(&self, new_bom: NewBOM) -> Result<VersionedBOM, ServiceError> {
+        self.ensure_rule_set_allowed(new_bom.category.as_deref(), new_bom.rule_set.as_deref())?;
+        let events = self.resolve_changes(new_bom.events)?;
+        self.create_bom(
+            events,
+            new_bom.metadata,
+            new_bom.category,
+            new_bom.rule_set,
+            None,
+        )
+    }
 
-        println!("{:?}", components);
+    /// Creates a new BOM with the content of `version` of `bom_id`, or of its
+    /// current state, and remembers where it came from.
+    pub fn clone_bom(
+        &self,
+        bom_id: Uuid,
+        version: Option<i32>,
+        name: Option<String>,
+        metadata: VersionMetadata,
+    ) -> Result<VersionedBOM, ServiceError> {
+        let source = match version {
+            Some(version) => self.reconstruct_bom(bom_id, version)?,
+            None => self.find_bom_by_id(bom_id)?,
+        };
 
-        Ok(BOM::from((bom, components)))
+        let mut events = vec![BOMChangeEvent::NameChanged(
+            name.unwrap_or_else(|| source.name.clone()),
+        )];
+        if let Some(description) = source.description {
+            events.push(BOMChangeEvent::DescriptionChanged(description));
+        }
+        events.extend(
+            source
+                .components
+                .into_iter()
+                .map(|cc| BOMChangeEvent::ComponentAdded(cc.component, cc.quantity)),
+        );
+
+        let metadata = VersionMetadata {
+            message: metadata.message.or_else(|| {
+                Some(format!(
+                    "Cloned from version {} of BOM {}",
+                    source.version, bom_id
+                ))
+            }),
+            ..metadata
+        };
+
+        let current = self.find_bom_by_id(bom_id)?;
+
+        self.create_bom(
+            events,
+            metadata,
+            current.category,
+            current.rule_set,
+            Some((bom_id, source.version)),
+        )
+    }
+
+    pub fn get_lineage(&self, bom_id: Uuid) -> Result<BOMLineage, ServiceError> {
+        self.repo.find_by_id(bom_id)?;
+
+        let mut ancestors: Vec<LineageLink> = vec![];
+        let mut current = bom_id;
+        while let Some(link) = self.repo.find_lineage(current)? {
+            current = link.source_bom_id;
+            ancestors.push(link.into());
+        }
+
+        let mut descendants: Vec<LineageLink> = vec![];
+        let mut generation = vec![bom_id];
+        while !generation.is_empty() {
+            let clones = self.repo.find_clones_of(&generation)?;
+            generation = clones.iter().map(|clone| clone.bom_id).collect();
+            descendants.extend(clones.into_iter().map(LineageLink::from));
+        }
+
+        Ok(BOMLineage {
+            bom_id,
+            ancestors,
+            descendants,
+        })
     }
 
     pub fn update_bom(
         &self,
         bom_id: Uuid,
-        change_events: Box<Vec<BOMChangeEvent>>,
+        update: BOMUpdate,
         operation: UpdateOperation,
-    ) -> Result<BOM, ServiceError> {
-        let mut bom = BOM::from(self.repo.find_by_id(bom_id)?);
+    ) -> Result<VersionedBOM, ServiceError> {
+        let BOMUpdate {
+            events,
+            expected_version,
+            metadata,
+        } = update;
+        let events = self.resolve_changes(events)?;
 
-        if let UpdateOperation::Revert = operation {
-            bom.clean_for_revert();
-        }
+        self.write_version(bom_id, expected_version, metadata, operation, |_| {
+            Ok(events.clone())
+        })
+    }
 
-        bom.increment_version();
+    /// Runs an update on a copy of the BOM and reports what it would change,
+    /// without writing anything.
+    pub fn preview_update(
+        &self,
+        bom_id: Uuid,
+        update: BOMUpdate,
+    ) -> Result<BOMPreview, ServiceError> {
+        let BOMUpdate {
+            events,
+            expected_version,
+            ..
+        } = update;
+        let events = self.resolve_changes(events)?;
 
-        let _ = change_events
-            .iter()
-            .try_for_each(|event| -> Result<(), ServiceError> {
-                bom.apply_change(event, BOMChangeEventValidator)?;
-                Ok(())
+        let current = BOM::from(self.repo.find_by_id(bom_id)?);
+        let mut bom = current.clone();
+        let events = self.apply_write(&mut bom, expected_version, &|_| Ok(events.clone()))?;
+
+        Ok(BOMPreview::new(&current, &events, bom))
+    }
+
+    /// Applies a JSON Patch to the current state of the BOM. The changes are
+    /// derived again from the state each write attempt is based on.
+    pub fn patch_bom(
+        &self,
+        bom_id: Uuid,
+        patch: BOMPatch,
+        expected_version: Option<i32>,
+        metadata: VersionMetadata,
+    ) -> Result<VersionedBOM, ServiceError> {
+        self.write_version(
+            bom_id,
+            expected_version,
+            metadata,
+            UpdateOperation::Incremental,
+            |current| self.resolve_changes(patch.to_change_requests(current)?),
+        )
+    }
+
+    pub fn revert_bom_to_version(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+        metadata: VersionMetadata,
+    ) -> Result<VersionedBOM, ServiceError> {
+        let metadata = VersionMetadata {
+            message: metadata
+                .message
+                .or_else(|| Some(format!("Revert to version {}", version))),
+            ..metadata
+        };
+
+        self.write_version(
+            bom_id,
+            None,
+            metadata,
+            UpdateOperation::Revert(version),
+            |current| {
+                let target = self.reconstruct_bom(bom_id, version)?;
+                Ok(Vec::<BOMChangeEvent>::from(&BOMDiff::between(
+                    current, &target,
+                )))
+            },
+        )
+    }
+
+    /// Applies the events of a version of another BOM to `bom_id`. Nothing is
+    /// written unless every event applies to the current state of the target.
+    /// Name and description changes are left out unless asked for.
+    pub fn cherry_pick(
+        &self,
+        bom_id: Uuid,
+        cherry_pick: CherryPick,
+    ) -> Result<VersionedBOM, ServiceError> {
+        let CherryPick {
+            source_bom_id,
+            version,
+            include_header_changes,
+            metadata,
+        } = cherry_pick;
+
+        let source: BOM = self.repo.find_by_id(source_bom_id)?.into();
+        self.ensure_version_exists(&source, version)?;
+
+        let events = self
+            .repo
+            .find_bom_version(source_bom_id, version)?
+            .map(BomVersion::try_from)
+            .transpose()?
+            .map(|picked| *picked.changes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|event| include_header_changes || !event.is_header_change())
+            .collect::<Vec<_>>();
+
+        let metadata = VersionMetadata {
+            message: metadata.message.or_else(|| {
+                Some(format!(
+                    "Cherry-pick of version {} of BOM {}",
+                    version, source_bom_id
+                ))
+            }),
+            ..metadata
+        };
+
+        self.write_version(bom_id, None, metadata, UpdateOperation::Incremental, |_| {
+            Ok(events.clone())
+        })
+    }
+
+    /// Applies a diff to `bom_id`. Nothing is written if an entry of the diff
+    /// expects another state than the current one of the target.
+    pub fn apply_diff(
+        &self,
+        bom_id: Uuid,
+        apply_diff: ApplyDiff,
+        expected_version: Option<i32>,
+    ) -> Result<VersionedBOM, ServiceError> {
+        let ApplyDiff { diff, metadata } = apply_diff;
+        let requests: Vec<BOMChangeRequest> = Vec::<BOMChangeEvent>::from(&diff)
+            .into_iter()
+            .map(BOMChangeRequest::from)
+            .collect();
+        let events = self.resolve_changes(requests)?;
+
+        self.write_version(
+            bom_id,
+            expected_version,
+            metadata,
+            UpdateOperation::Incremental,
+            |current| {
+                let conflicts = diff.conflicts_with(current);
+                if !conflicts.is_empty() {
+                    return Err(ServiceError::DiffConflicts(conflicts));
+                }
+                Ok(events.clone())
+            },
+        )
+    }
+
+    /// Runs `write` at most once per idempotency key and answers replays of
+    /// the same request with the response of the first one.
+    pub fn write_idempotently<F>(
+        &self,
+        key: Option<IdempotencyKey>,
+        write: F,
+    ) -> Result<IdempotentWrite, ServiceError>
+    where
+        F: FnOnce() -> Result<VersionedBOM, ServiceError>,
+    {
+        let Some(key) = key else {
+            return write().map(|bom| IdempotentWrite {
+                bom,
+                replayed: false,
             });
+        };
 
-        let new_bom_components = self.transform_counted_components(&bom_id, &bom.components);
+        let now = Utc::now();
+        let expired_before = now - Duration::hours(self.settings.idempotency_retention_hours);
+        // A request that died before storing its response leaves its key
+        // reserved until the timeout passes
+        let abandoned_before =
+            now - Duration::seconds(self.settings.idempotency_reservation_timeout_seconds);
 
-        let new_bom_version: DbBomVersion =
-            BomVersion::new(&bom.id, bom.version, change_events).try_into()?;
+        if let Some(record) = self.repo.reserve_idempotency_key(
+            &DbIdempotencyKey::from(&key),
+            expired_before,
+            abandoned_before,
+        )? {
+            let record = IdempotencyRecord::try_from(record)?;
 
-        let bom: DbBOM = bom.into();
+            if record.request_hash != key.request_hash {
+                return Err(ServiceError::IdempotencyKeyReused(format!(
+                    "Idempotency-Key {} was already used for a different request",
+                    key.key
+                )));
+            }
+
+            return match record.response {
+                Some(bom) => Ok(IdempotentWrite {
+                    bom,
+                    replayed: true,
+                }),
+                None => Err(ServiceError::RequestInProgress(format!(
+                    "A request with Idempotency-Key {} is still being processed",
+                    key.key
+                ))),
+            };
+        }
+
+        match write() {
+            Ok(bom) => {
+                let response = serde_json::to_value(&bom)
+                    .map_err(|e| DomainError::ConversionError(e.to_string()))?;
+                self.repo.complete_idempotency_key(&key.key, &response)?;
 
-        let (updated_bom, components) =
+                Ok(IdempotentWrite {
+                    bom,
+                    replayed: false,
+                })
+            }
+            Err(error) => {
+                self.repo.release_idempotency_key(&key.key)?;
+                Err(error)
+            }
+        }
+    }
+
+    pub fn get_version_history(
+        &self,
+        bom_id: Uuid,
+        page: i64,
+        per_page: i64,
+    ) -> Result<VersionHistory, ServiceError> {
+        if page < 1 || !(1..=100).contains(&per_page) {
+            return Err(ServiceError::InvalidData(
+                "Page must be at least 1 and per_page between 1 and 100".to_string(),
+            ));
+        }
+
+        self.repo.find_by_id(bom_id)?;
+
+        let (versions, total) =
             self.repo
-                .update_and_archive(bom_id, &bom, &new_bom_components, &new_bom_version)?;
+                .get_bom_versions_page(bom_id, (page - 1) * per_page, per_page)?;
 
-        Ok(BOM::from((updated_bom, components)))
+        let versions = versions
+            .into_iter()
+            .map(|version| {
+                BomVersion::try_from(version)
+                    .map(VersionSummary::from)
+                    .map_err(ServiceError::from)
+            })
+            .collect::<Result<Vec<VersionSummary>, ServiceError>>()?;
+
+        Ok(VersionHistory {
+            bom_id,
+            page,
+            per_page,
+            total,
+            versions,
+        })
     }
 
-    pub fn revert_bom_to_version(&self, bom_id: Uuid, version: i32) -> Result<BOM, ServiceError> {
+    /// Annotates the BOM at `version`, or at its current version, with the
+    /// versions that introduced and last changed each part of it.
+    pub fn blame_bom(&self, bom_id: Uuid, version: Option<i32>) -> Result<BOMBlame, ServiceError> {
+        let bom: BOM = self.repo.find_by_id(bom_id)?.into();
+        let version = version.unwrap_or(bom.version);
+
+        self.ensure_version_exists(&bom, version)?;
+
         let versions = self.fetch_bom_versions_until_version(bom_id, version)?;
 
-        let mut change_events: Box<Vec<BOMChangeEvent>> = Box::default();
+        Ok(BOMBlame::from_versions(bom, &versions)?)
+    }
+
+    /// Runs the lint checks on `version` of the BOM, or on its current state.
+    pub fn lint_bom(&self, bom_id: Uuid, version: Option<i32>) -> Result<BOMLint, ServiceError> {
+        let bom = match version {
+            Some(version) => self.reconstruct_bom(bom_id, version)?,
+            None => self.find_bom_by_id(bom_id)?,
+        };
 
-        versions.into_iter().for_each(|version| {
-            version.changes.into_iter().for_each(|change_event| {
-                change_events.push(change_event);
+        let component_ids: Vec<Uuid> = bom.components.iter().map(|cc| cc.component.id).collect();
+        let lifecycles = self
+            .repo
+            .find_component_lifecycles(&component_ids)?
+            .into_iter()
+            .map(|lifecycle| {
+                ComponentLifecycle::try_from(lifecycle)
+                    .map(|lifecycle| (lifecycle.component_id, lifecycle.status))
             })
-        });
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(BOMLint::run(&bom, &lifecycles))
+    }
+
+    /// Labels a version, e.g. as a release. Tagged versions cannot be squashed.
+    pub fn tag_bom_version(
+        &self,
+        bom_id: Uuid,
+        version: i32,
+        tag: &str,
+    ) -> Result<VersionSummary, ServiceError> {
+        let tag = tag.trim();
+        if tag.is_empty() || tag.len() > 64 {
+            return Err(ServiceError::InvalidData(
+                "Tag must be between 1 and 64 characters".to_string(),
+            ));
+        }
+
+        let bom: BOM = self.repo.find_by_id(bom_id)?.into();
+        self.ensure_version_exists(&bom, version)?;
+
+        if let Some(tagged) = self
+            .repo
+            .find_tagged_bom_versions(bom_id)?
+            .into_iter()
+            .find(|tagged| tagged.tag.as_deref() == Some(tag) && tagged.version != version)
+        {
+            return Err(ServiceError::InvalidData(format!(
+                "Tag {} is already used by version {}",
+                tag, tagged.version
+            )));
+        }
+
+        let tagged = self.repo.tag_bom_version(bom_id, version, tag)?;
 
-        self.update_bom(bom_id, change_events, UpdateOperation::Revert)
+        Ok(VersionSummary::from(BomVersion::try_from(tagged)?))
     }
 
+    /// Folds versions `from` to `to` into version `to`, whose events become the
+    /// smallest set of changes leading from the state before `from` to the
+    /// state at `to`. Later versions keep their numbers.
+    pub fn squash_bom_versions(
+        &self,
+        bom_id: Uuid,
+        from: i32,
+        to: i32,
+    ) -> Result<VersionSummary, ServiceError> {
+        if from >= to {
+            return Err(ServiceError::InvalidData(
+                "Squashing needs a range of at least two versions".to_string(),
+            ));
+        }
+
+        let bom: BOM = self.repo.find_by_id(bom_id)?.into();
+
+        // The squash is computed under the lock of the BOM, from the versions
+        // read in the same transaction. A rejection rolls the transaction back.
+        let mut rejection = None;
+        let squashed =
+            self.repo
+                .squash_bom_versions(bom_id, from, to, &mut |current_version, versions| {
+                    self.squash(&bom, current_version, from, to, versions)
+                        .map_err(|error| {
+                            rejection = Some(error);
+                            DatabaseError::from(DieselError::RollbackTransaction)
+                        })
+                });
+
+        if let Some(error) = rejection {
+            return Err(error);
+        }
+
+        Ok(VersionSummary::from(BomVersion::try_from(squashed?)?))
+    }
+
+    /// Folds versions `from` to `to` of `bom` into one. `versions` are the
+    /// versions up to `to`, read while the BOM is at `current_version`.
+    fn squash(
+        &self,
+        bom: &BOM,
+        current_version: i32,
+        from: i32,
+        to: i32,
+        versions: Vec<DbBomVersion>,
+    ) -> Result<DbBomVersion, ServiceError> {
+        for version in [from, to] {
+            Self::ensure_version_in_range(current_version, version)?;
+            let found = versions
+                .iter()
+                .find(|stored| stored.version >= version)
+                .map(|stored| stored.version);
+            Self::ensure_version_not_squashed(found, version)?;
+        }
+
+        let versions = versions
+            .into_iter()
+            .map(BomVersion::try_from)
+            .collect::<Result<Vec<BomVersion>, _>>()?;
+
+        if let Some(tagged) = versions
+            .iter()
+            .find(|version| version.tag.is_some() && (from..to).contains(&version.version))
+        {
+            return Err(ServiceError::InvalidData(format!(
+                "Version {} is tagged as {} and cannot be squashed",
+                tagged.version,
+                tagged.tag.clone().unwrap_or_default()
+            )));
+        }
+
+        let (earlier, squashed): (Vec<BomVersion>, Vec<BomVersion>) = versions
+            .into_iter()
+            .partition(|version| version.version < from);
+
+        let mut before = bom.clone();
+        before.clean_for_revert();
+        before.name = String::new();
+        self.replay(&mut before, &earlier)?;
+
+        let mut after = before.clone();
+        self.replay(&mut after, &squashed)?;
+
+        let changes = Vec::<BOMChangeEvent>::from(&BOMDiff::between(&before, &after));
+
+        let surviving = squashed
+            .into_iter()
+            .last()
+            .filter(|surviving| surviving.version == to)
+            .ok_or_else(|| ServiceError::InvalidData(format!("Version {} not found", to)))?;
+
+        Ok(surviving.as_squash_of(from, changes).try_into()?)
+    }
+
+    /// Compares two versions of a BOM. `from` may be newer than `to`, in which
+    /// case the diff describes how to go back in history.
     pub fn get_bom_diff(&self, bom_id: Uuid, from: i32, to: i32) -> Result<BOMDiff, ServiceError> {
-        let versions = self.fetch_bom_versions_until_version(bom_id, to)?;
+        let starting_bom = self.reconstruct_bom(bom_id, from)?;
+        let ending_bom = self.reconstruct_bom(bom_id, to)?;
 
-        let mut events_until_starting_bom = vec![];
-        let mut events_until_ending_bom = vec![];
+        Ok(BOMDiff::between(&starting_bom, &ending_bom))
+    }
 
-        versions.into_iter().enumerate().for_each(|(i, version)| {
-            if i <= (from - 1) as usize {
-                version.changes.into_iter().for_each(|change_event| {
-                    events_until_starting_bom.push(change_event);
-                })
-            } else {
-                version.changes.into_iter().for_each(|change_event| {
-                    events_until_ending_bom.push(change_event);
-                })
-            }
-        });
+    /// Compares two BOMs, each either at one of its versions or in its current
+    /// state.
+    pub fn compare_boms(
+        &self,
+        left: BOMReference,
+        right: BOMReference,
+        matching: ComponentMatching,
+    ) -> Result<BOMDiff, ServiceError> {
+        let left_bom = self.resolve_bom_reference(left)?;
+        let right_bom = self.resolve_bom_reference(right)?;
 
-        let starting_bom = BOM::try_from(&NewBOM::new(events_until_starting_bom))?;
+        Ok(BOMDiff::between_matching(&left_bom, &right_bom, matching))
+    }
 
-        let diff = BOMDiff::from((&starting_bom, &events_until_ending_bom));
+    /// Replays the full event log of a BOM and compares the result with every
+    /// stored snapshot.
+    pub fn check_snapshot_consistency(
+        &self,
+        bom_id: Uuid,
+    ) -> Result<SnapshotConsistency, ServiceError> {
+        let mut bom: BOM = self.repo.find_by_id(bom_id)?.into();
+
+        let snapshots = self
+            .repo
+            .find_snapshots(bom_id)?
+            .into_iter()
+            .map(|snapshot| BomSnapshot::try_from(snapshot).map_err(ServiceError::from))
+            .collect::<Result<Vec<BomSnapshot>, ServiceError>>()?;
 
-        Ok(diff)
+        let versions = self.fetch_bom_versions_until_version(bom_id, bom.version)?;
+        let mut pending_versions = versions.iter().peekable();
+
+        bom.clean_for_revert();
+
+        let mut checked_versions = vec![];
+        let mut mismatched_versions = vec![];
+
+        for snapshot in snapshots.iter() {
+            while let Some(version) =
+                pending_versions.next_if(|version| version.version <= snapshot.version)
+            {
+                self.replay(&mut bom, std::slice::from_ref(version))?;
+            }
+            bom.version = snapshot.version;
+
+            checked_versions.push(snapshot.version);
+            if !bom.has_same_content(&snapshot.bom) {
+                mismatched_versions.push(snapshot.version);
+            }
+        }
+
+        Ok(SnapshotConsistency::new(
+            bom_id,
+            checked_versions,
+            mismatched_versions,
+        ))
     }
 }
 
@@ -207,6 +843,25 @@ impl BomService {
         ))
     }
 
+    pub fn set_component_lifecycle(
+        &self,
+        component_id: Uuid,
+        status: LifecycleStatus,
+    ) -> Result<ComponentLifecycle, ServiceError> {
+        self.repo.find_component_by_id(component_id)?;
+
+        let lifecycle = ComponentLifecycle {
+            component_id,
+            status,
+            updated_at: Utc::now(),
+        };
+
+        Ok(self
+            .repo
+            .upsert_component_lifecycle(&(&lifecycle).into())?
+            .try_into()?)
+    }
+
     pub fn search_components(
         &self,
         query_string: &str,
@@ -221,6 +876,310 @@ impl BomService {
 }
 
 impl BomService {
+    fn create_bom(
+        &self,
+        events: Vec<BOMChangeEvent>,
+        metadata: VersionMetadata,
+        category: Option<String>,
+        rule_set: Option<String>,
+        cloned_from: Option<(Uuid, i32)>,
+    ) -> Result<VersionedBOM, ServiceError> {
+        let events =
+            normalize_events(&BOM::default(), &events).map_err(ServiceError::EventsRejected)?;
+        let mut bom: BOM = BOM::try_from(events.as_slice())?;
+        bom.category = category;
+        bom.rule_set = rule_set;
+
+        self.enforce_rules(&events, &bom)?;
+
+        let new_bom_components = self.transform_counted_components(&bom.id, &bom.components);
+
+        let new_bom_version: DbBomVersion =
+            BomVersion::new(&bom.id, bom.version, Box::new(events), metadata.clone()).try_into()?;
+
+        let lineage: Option<DbBomLineage> = cloned_from.map(|(source_bom_id, source_version)| {
+            (&LineageLink::new(bom.id, source_bom_id, source_version)).into()
+        });
+
+        let (bom, components) = self.repo.insert(
+            &bom.into(),
+            &new_bom_components,
+            &new_bom_version,
+            lineage.as_ref(),
+        )?;
+
+        Ok(VersionedBOM::new(BOM::from((bom, components)), metadata))
+    }
+
+    /// Appends a new version made of the events `events_for` derives from the
+    /// current state of the BOM. A write that loses a race against a concurrent
+    /// one is retried on top of the state that won.
+    fn write_version<F>(
+        &self,
+        bom_id: Uuid,
+        expected_version: Option<i32>,
+        metadata: VersionMetadata,
+        operation: UpdateOperation,
+        events_for: F,
+    ) -> Result<VersionedBOM, ServiceError>
+    where
+        F: Fn(&BOM) -> Result<Vec<BOMChangeEvent>, ServiceError>,
+    {
+        let mut attempt = 1;
+        loop {
+            match self.try_write_version(
+                bom_id,
+                expected_version,
+                &metadata,
+                operation,
+                &events_for,
+            ) {
+                Err(ServiceError::DatabaseError(error))
+                    if error.is_write_conflict() && attempt < MAX_WRITE_ATTEMPTS =>
+                {
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn try_write_version<F>(
+        &self,
+        bom_id: Uuid,
+        expected_version: Option<i32>,
+        metadata: &VersionMetadata,
+        operation: UpdateOperation,
+        events_for: &F,
+    ) -> Result<VersionedBOM, ServiceError>
+    where
+        F: Fn(&BOM) -> Result<Vec<BOMChangeEvent>, ServiceError>,
+    {
+        let mut bom = BOM::from(self.repo.find_by_id(bom_id)?);
+        let base_version = bom.version;
+        let events = self.apply_write(&mut bom, expected_version, events_for)?;
+
+        let new_bom_components = self.transform_counted_components(&bom_id, &bom.components);
+
+        let mut new_bom_version =
+            BomVersion::new(&bom.id, bom.version, Box::new(events), metadata.clone());
+
+        if let UpdateOperation::Revert(version) = operation {
+            new_bom_version = new_bom_version.as_revert_of(version);
+        }
+
+        let new_bom_version: DbBomVersion = new_bom_version.try_into()?;
+
+        let new_bom_snapshot: Option<DbBomSnapshot> = if self.is_snapshot_version(bom.version) {
+            Some(BomSnapshot::new(&bom).try_into()?)
+        } else {
+            None
+        };
+
+        let bom: DbBOM = bom.into();
+
+        let (updated_bom, components) = self.repo.update_and_archive(
+            bom_id,
+            base_version,
+            &bom,
+            &new_bom_components,
+            &new_bom_version,
+            new_bom_snapshot.as_ref(),
+        )?;
+
+        Ok(VersionedBOM::new(
+            BOM::from((updated_bom, components)),
+            metadata.clone(),
+        ))
+    }
+
+    /// Checks that `bom` accepts a write and applies the events `events_for`
+    /// derives from it. Returns the events as they are to be stored.
+    fn apply_write<F>(
+        &self,
+        bom: &mut BOM,
+        expected_version: Option<i32>,
+        events_for: &F,
+    ) -> Result<Vec<BOMChangeEvent>, ServiceError>
+    where
+        F: Fn(&BOM) -> Result<Vec<BOMChangeEvent>, ServiceError>,
+    {
+        let base_version = bom.version;
+
+        if bom.archived_at.is_some() {
+            return Err(ServiceError::InvalidState(format!(
+                "BOM {} is archived and cannot be changed",
+                bom.id
+            )));
+        }
+
+        if let Some(expected) = expected_version {
+            if expected != base_version {
+                return Err(ServiceError::VersionConflict {
+                    expected,
+                    current: base_version,
+                });
+            }
+        }
+
+        let events =
+            normalize_events(bom, &events_for(bom)?).map_err(ServiceError::EventsRejected)?;
+
+        bom.increment_version();
+
+        for event in events.iter() {
+            bom.apply_change(event, BOMChangeEventValidator)?;
+        }
+
+        self.enforce_rules(&events, bom)?;
+
+        Ok(events)
+    }
+
+    /// Rejects rule sets that do not exist and ones that differ from the rule
+    /// set of the category, so that a client cannot loosen its category's
+    /// policy.
+    fn ensure_rule_set_allowed(
+        &self,
+        category: Option<&str>,
+        rule_set: Option<&str>,
+    ) -> Result<(), ServiceError> {
+        let Some(rule_set) = rule_set else {
+            return Ok(());
+        };
+        let rules = &self.settings.rules;
+
+        if !rules.rule_sets.contains_key(rule_set) {
+            return Err(ServiceError::InvalidData(format!(
+                "Unknown rule set {}",
+                rule_set
+            )));
+        }
+
+        match rules.category_rule_set(category) {
+            Some(required) if required != rule_set => Err(ServiceError::InvalidData(format!(
+                "BOMs of category {} use rule set {} and cannot use {}",
+                category.unwrap_or_default(),
+                required,
+                rule_set
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails with every rule of the BOM's rule set that `events`, leading to
+    /// `bom`, break.
+    fn enforce_rules(&self, events: &[BOMChangeEvent], bom: &BOM) -> Result<(), ServiceError> {
+        let rules = &self.settings.rules;
+        let name = rules.rule_set_name(bom.rule_set.as_deref(), bom.category.as_deref());
+        let rule_set = rules
+            .rule_sets
+            .get(name)
+            .ok_or_else(|| ServiceError::InvalidData(format!("Unknown rule set {}", name)))?;
+
+        let violations = check_rules(rule_set, events, bom);
+        if !violations.is_empty() {
+            return Err(ServiceError::RulesViolated(violations));
+        }
+
+        Ok(())
+    }
+
+    /// Turns change requests into events carrying the catalog entries of the
+    /// components they reference.
+    fn resolve_changes(
+        &self,
+        requests: Vec<BOMChangeRequest>,
+    ) -> Result<Vec<BOMChangeEvent>, ServiceError> {
+        let mut ids = vec![];
+        let mut part_numbers = vec![];
+        let references = requests.iter().flat_map(|request| match request {
+            BOMChangeRequest::ComponentAdded(reference, _)
+            | BOMChangeRequest::ComponentRemoved(reference) => vec![reference],
+            BOMChangeRequest::ComponentReplaced { old, new, .. } => vec![old, new],
+            _ => vec![],
+        });
+        for reference in references {
+            match reference {
+                ComponentReference::Id { id } => ids.push(*id),
+                ComponentReference::PartNumber { part_number } => {
+                    part_numbers.push(part_number.clone())
+                }
+            }
+        }
+
+        let catalog: Vec<DomainComponent> = if ids.is_empty() && part_numbers.is_empty() {
+            vec![]
+        } else {
+            self.repo
+                .find_components_by_ids_or_part_numbers(&ids, &part_numbers)?
+                .into_iter()
+                .map(DomainComponent::from)
+                .collect()
+        };
+
+        let resolve = |index: usize, reference: &ComponentReference| {
+            let matches: Vec<&DomainComponent> = catalog
+                .iter()
+                .filter(|component| match reference {
+                    ComponentReference::Id { id } => component.id == *id,
+                    ComponentReference::PartNumber { part_number } => {
+                        component.part_number == *part_number
+                    }
+                })
+                .collect();
+
+            match matches.as_slice() {
+                [component] => Ok((*component).clone()),
+                [] => Err(format!("Event {}: no component with {}", index, reference)),
+                _ => Err(format!(
+                    "Event {}: {} matches {} components",
+                    index,
+                    reference,
+                    matches.len()
+                )),
+            }
+        };
+
+        let mut unresolved = vec![];
+        let events = requests
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, request)| {
+                let event = match request {
+                    BOMChangeRequest::NameChanged(name) => Ok(BOMChangeEvent::NameChanged(name)),
+                    BOMChangeRequest::DescriptionChanged(description) => {
+                        Ok(BOMChangeEvent::DescriptionChanged(description))
+                    }
+                    BOMChangeRequest::DescriptionCleared => Ok(BOMChangeEvent::DescriptionCleared),
+                    BOMChangeRequest::ComponentAdded(reference, qty) => resolve(index, &reference)
+                        .map(|component| BOMChangeEvent::ComponentAdded(component, qty)),
+                    BOMChangeRequest::ComponentRemoved(reference) => {
+                        resolve(index, &reference).map(BOMChangeEvent::ComponentRemoved)
+                    }
+                    BOMChangeRequest::ComponentUpdated(id, qty) => {
+                        Ok(BOMChangeEvent::ComponentUpdated(id, qty))
+                    }
+                    BOMChangeRequest::ComponentReplaced { old, new, qty } => resolve(index, &old)
+                        .and_then(|old| {
+                            resolve(index, &new).map(|new| BOMChangeEvent::ComponentReplaced {
+                                old,
+                                new,
+                                qty,
+                            })
+                        }),
+                };
+                event.map_err(|reason| unresolved.push(reason)).ok()
+            })
+            .collect();
+
+        if !unresolved.is_empty() {
+            return Err(ServiceError::UnresolvedComponents(unresolved));
+        }
+
+        Ok(events)
+    }
+
     fn transform_counted_components(
         &self,
         bom_id: &Uuid,
@@ -228,10 +1187,94 @@ impl BomService {
     ) -> Vec<BomComponent> {
         counted_components
             .iter()
-            .map(|counted_component| BomComponent::from((bom_id, counted_component)))
+            .enumerate()
+            .map(|(position, counted_component)| {
+                BomComponent::from((bom_id, position as i32, counted_component))
+            })
             .collect()
     }
 
+    /// Rebuilds the BOM as it was at `version`, starting from the nearest
+    /// snapshot when there is one and from the first version otherwise.
+    fn reconstruct_bom(&self, bom_id: Uuid, version: i32) -> Result<BOM, ServiceError> {
+        let mut bom: BOM = self.repo.find_by_id(bom_id)?.into();
+
+        self.ensure_version_exists(&bom, version)?;
+
+        let versions = match self
+            .repo
+            .find_latest_snapshot_until_version(bom_id, version)?
+        {
+            Some(snapshot) => {
+                let snapshot = BomSnapshot::try_from(snapshot)?;
+                bom.restore_content_from(snapshot.bom);
+                self.fetch_bom_versions_after_version(bom_id, snapshot.version, version)?
+            }
+            None => {
+                bom.clean_for_revert();
+                self.fetch_bom_versions_until_version(bom_id, version)?
+            }
+        };
+
+        self.replay(&mut bom, &versions)?;
+        bom.version = version;
+
+        Ok(bom)
+    }
+
+    fn ensure_version_exists(&self, bom: &BOM, version: i32) -> Result<(), ServiceError> {
+        Self::ensure_version_in_range(bom.version, version)?;
+        let found = self.repo.find_bom_version(bom.id, version)?;
+        Self::ensure_version_not_squashed(found.map(|found| found.version), version)
+    }
+
+    fn ensure_version_in_range(latest: i32, version: i32) -> Result<(), ServiceError> {
+        if version < 1 || version > latest {
+            return Err(ServiceError::InvalidData(format!(
+                "Version {} is out of range. Available versions are 1 to {}",
+                version, latest
+            )));
+        }
+        Ok(())
+    }
+
+    /// `found` is the version stored for `version`, which is a later one when
+    /// `version` was squashed into it
+    fn ensure_version_not_squashed(found: Option<i32>, version: i32) -> Result<(), ServiceError> {
+        match found {
+            Some(found) if found == version => Ok(()),
+            _ => Err(ServiceError::InvalidData(format!(
+                "Version {} was squashed into a later version",
+                version
+            ))),
+        }
+    }
+
+    fn resolve_bom_reference(&self, reference: BOMReference) -> Result<BOM, ServiceError> {
+        match reference.version {
+            Some(version) => self.reconstruct_bom(reference.bom_id, version),
+            None => self.find_bom_by_id(reference.bom_id),
+        }
+    }
+
+    fn replay(&self, bom: &mut BOM, versions: &[BomVersion]) -> Result<(), ServiceError> {
+        for version in versions.iter() {
+            for change_event in version.changes.iter() {
+                bom.apply_change(change_event, BOMChangeEventValidator)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// BOMs deleted before this instant can no longer be restored.
+    fn restore_deadline(&self) -> DateTime<Utc> {
+        Utc::now() - Duration::days(self.settings.restore_window_days)
+    }
+
+    fn is_snapshot_version(&self, version: i32) -> bool {
+        self.settings.snapshot_interval > 0 && version % self.settings.snapshot_interval == 0
+    }
+
     fn fetch_bom_versions_until_version(
         &self,
         bom_id: Uuid,
@@ -243,4 +1286,17 @@ impl BomService {
             .map(|version| BomVersion::try_from(version).map_err(ServiceError::from))
             .collect::<Result<Vec<BomVersion>, ServiceError>>()
     }
+
+    fn fetch_bom_versions_after_version(
+        &self,
+        bom_id: Uuid,
+        after_version: i32,
+        until_version: i32,
+    ) -> Result<Vec<BomVersion>, ServiceError> {
+        self.repo
+            .get_bom_versions_after_version(bom_id, after_version, until_version)?
+            .into_iter()
+            .map(|version| BomVersion::try_from(version).map_err(ServiceError::from))
+            .collect::<Result<Vec<BomVersion>, ServiceError>>()
+    }
 }
@@ -4,14 +4,19 @@ use uuid::Uuid;
 use crate::{
     domain::{
         error::DomainError,
-        newtypes::{new_bom::NewBOM, new_component::NewComponent},
+        newtypes::{idempotency_key::IdempotencyKey, new_component::NewComponent},
+        upcasting::{upcast_bom, upcast_events, CURRENT_EVENT_SCHEMA_VERSION},
         validation::BOMChangeEventValidator,
-        BOMChangeEvent, BomVersion as DomainBomVersion, Component as DomainComponent,
-        CountedComponent, Price, BOM,
+        BOMChangeEvent, BomSnapshot as DomainBomSnapshot, BomVersion as DomainBomVersion,
+        Component as DomainComponent, ComponentLifecycle as DomainComponentLifecycle,
+        CountedComponent, IdempotencyRecord, LineageLink, Price, VersionMetadata, BOM,
     },
     infrastructure::models::{
-        bom::BOM as DbBOM, bom_components::BomComponent, bom_version::BomVersion as DbBomVersion,
+        bom::BOM as DbBOM, bom_components::BomComponent, bom_lineage::BomLineage as DbBomLineage,
+        bom_snapshot::BomSnapshot as DbBomSnapshot, bom_version::BomVersion as DbBomVersion,
         component::Component as DbComponent,
+        component_lifecycle::ComponentLifecycle as DbComponentLifecycle,
+        idempotency_key::IdempotencyKey as DbIdempotencyKey,
     },
 };
 
@@ -33,11 +38,11 @@ impl From<NewComponent> for DbComponent {
     }
 }
 
-impl TryFrom<&NewBOM> for BOM {
+impl TryFrom<&[BOMChangeEvent]> for BOM {
     type Error = DomainError;
 
-    fn try_from(value: &NewBOM) -> Result<Self, Self::Error> {
-        if value.events.is_empty() {
+    fn try_from(value: &[BOMChangeEvent]) -> Result<Self, Self::Error> {
+        if value.is_empty() {
             return Err(DomainError::ValidationError(
                 "Unable to construct BOM without input".to_string(),
             ));
@@ -45,7 +50,6 @@ impl TryFrom<&NewBOM> for BOM {
 
         // Check if events include a NameChanged event
         if !value
-            .events
             .iter()
             .any(|event| matches!(event, BOMChangeEvent::NameChanged(_)))
         {
@@ -55,7 +59,7 @@ impl TryFrom<&NewBOM> for BOM {
         }
 
         let mut bom = BOM::default();
-        for event in value.events.iter() {
+        for event in value.iter() {
             bom.apply_change(event, BOMChangeEventValidator)?;
         }
         Ok(bom)
@@ -80,6 +84,9 @@ impl From<(DbBOM, Vec<(DbComponent, i32)>)> for BOM {
                 .collect(),
             created_at: bom.created_at,
             updated_at: bom.updated_at,
+            archived_at: bom.archived_at,
+            category: bom.category,
+            rule_set: bom.rule_set,
         }
     }
 }
@@ -93,6 +100,10 @@ impl From<BOM> for DbBOM {
             description: value.description,
             created_at: value.created_at,
             updated_at: value.updated_at,
+            archived_at: value.archived_at,
+            deleted_at: None,
+            category: value.category,
+            rule_set: value.rule_set,
         }
     }
 }
@@ -108,6 +119,13 @@ impl TryFrom<DomainBomVersion> for DbBomVersion {
             changes: serde_json::to_value(value.changes)
                 .map_err(|e| DomainError::ConversionError(e.to_string()))?,
             created_at: Utc::now(),
+            author: value.metadata.author,
+            message: value.metadata.message,
+            ticket: value.metadata.ticket,
+            reverted_to: value.reverted_to,
+            tag: value.tag,
+            squashed_from: value.squashed_from,
+            schema_version: CURRENT_EVENT_SCHEMA_VERSION,
         })
     }
 }
@@ -120,7 +138,107 @@ impl TryFrom<DbBomVersion> for DomainBomVersion {
             id: value.id,
             bom_id: value.bom_id,
             version: value.version,
-            changes: serde_json::from_value(value.changes)
+            changes: serde_json::from_value(upcast_events(value.changes, value.schema_version)?)
+                .map_err(|e| DomainError::ConversionError(e.to_string()))?,
+            metadata: VersionMetadata {
+                author: value.author,
+                message: value.message,
+                ticket: value.ticket,
+            },
+            reverted_to: value.reverted_to,
+            tag: value.tag,
+            squashed_from: value.squashed_from,
+            created_at: value.created_at,
+        })
+    }
+}
+
+impl TryFrom<DomainBomSnapshot> for DbBomSnapshot {
+    type Error = DomainError;
+
+    fn try_from(value: DomainBomSnapshot) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            bom_id: value.bom_id,
+            version: value.version,
+            snapshot: serde_json::to_value(value.bom)
+                .map_err(|e| DomainError::ConversionError(e.to_string()))?,
+            created_at: value.created_at,
+            schema_version: CURRENT_EVENT_SCHEMA_VERSION,
+        })
+    }
+}
+
+impl TryFrom<DbBomSnapshot> for DomainBomSnapshot {
+    type Error = DomainError;
+
+    fn try_from(value: DbBomSnapshot) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value.id,
+            bom_id: value.bom_id,
+            version: value.version,
+            bom: serde_json::from_value(upcast_bom(value.snapshot, value.schema_version)?)
+                .map_err(|e| DomainError::ConversionError(e.to_string()))?,
+            created_at: value.created_at,
+        })
+    }
+}
+
+/**********************************************************
+****     Database lineage <-> Domain lineage links     ****
+**********************************************************/
+
+impl From<&LineageLink> for DbBomLineage {
+    fn from(value: &LineageLink) -> Self {
+        Self {
+            bom_id: value.bom_id,
+            source_bom_id: value.source_bom_id,
+            source_version: value.source_version,
+            created_at: value.created_at,
+        }
+    }
+}
+
+impl From<DbBomLineage> for LineageLink {
+    fn from(value: DbBomLineage) -> Self {
+        Self {
+            bom_id: value.bom_id,
+            source_bom_id: value.source_bom_id,
+            source_version: value.source_version,
+            created_at: value.created_at,
+        }
+    }
+}
+
+/**********************************************************
+****   Idempotency keys <-> Database idempotency keys  ****
+**********************************************************/
+
+impl From<&IdempotencyKey> for DbIdempotencyKey {
+    fn from(value: &IdempotencyKey) -> Self {
+        Self {
+            key: value.key.clone(),
+            request_hash: value.request_hash.clone(),
+            response: None,
+            created_at: Utc::now(),
+            schema_version: CURRENT_EVENT_SCHEMA_VERSION,
+        }
+    }
+}
+
+impl TryFrom<DbIdempotencyKey> for IdempotencyRecord {
+    type Error = DomainError;
+
+    fn try_from(value: DbIdempotencyKey) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: value.key,
+            request_hash: value.request_hash,
+            response: value
+                .response
+                .map(|response| upcast_bom(response, value.schema_version))
+                .transpose()?
+                .map(serde_json::from_value)
+                .transpose()
                 .map_err(|e| DomainError::ConversionError(e.to_string()))?,
             created_at: value.created_at,
         })
@@ -161,12 +279,35 @@ impl From<DomainComponent> for DbComponent {
     }
 }
 
-impl From<(&Uuid, &CountedComponent)> for BomComponent {
-    fn from(value: (&Uuid, &CountedComponent)) -> Self {
+impl From<&DomainComponentLifecycle> for DbComponentLifecycle {
+    fn from(value: &DomainComponentLifecycle) -> Self {
+        Self {
+            component_id: value.component_id,
+            status: value.status.as_str().to_string(),
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+impl TryFrom<DbComponentLifecycle> for DomainComponentLifecycle {
+    type Error = DomainError;
+
+    fn try_from(value: DbComponentLifecycle) -> Result<Self, Self::Error> {
+        Ok(Self {
+            component_id: value.component_id,
+            status: value.status.parse()?,
+            updated_at: value.updated_at,
+        })
+    }
+}
+
+impl From<(&Uuid, i32, &CountedComponent)> for BomComponent {
+    fn from(value: (&Uuid, i32, &CountedComponent)) -> Self {
         Self {
             bom_id: *value.0,
-            component_id: value.1.component.id,
-            quantity: value.1.quantity,
+            component_id: value.2.component.id,
+            quantity: value.2.quantity,
+            position: value.1,
         }
     }
 }
@@ -1,10 +1,32 @@
-use crate::{domain::error::DomainError, infrastructure::error::DatabaseError};
+use crate::{
+    domain::{error::DomainError, rules::RuleViolation, validation::RejectedEvent, DiffConflict},
+    infrastructure::error::DatabaseError,
+};
 
 #[derive(Debug)]
 pub enum ServiceError {
     DomainError(DomainError),
     DatabaseError(DatabaseError),
     InvalidData(String),
+    /// The BOM is no longer at the version the caller based its change on.
+    VersionConflict {
+        expected: i32,
+        current: i32,
+    },
+    /// An idempotency key was sent again with a different request.
+    IdempotencyKeyReused(String),
+    /// The first request with an idempotency key has not finished yet.
+    RequestInProgress(String),
+    /// The BOM is in a state that does not allow the operation.
+    InvalidState(String),
+    /// Events that cannot be applied to the current state of the BOM.
+    EventsRejected(Vec<RejectedEvent>),
+    /// Component references that match no single catalog component.
+    UnresolvedComponents(Vec<String>),
+    /// Rules of the BOM's rule set that a write would break.
+    RulesViolated(Vec<RuleViolation>),
+    /// Entries of a diff that do not match the BOM it is applied to.
+    DiffConflicts(Vec<DiffConflict>),
 }
 
 impl From<DomainError> for ServiceError {
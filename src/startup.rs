@@ -1,18 +1,28 @@
 use std::{net::TcpListener, sync::Arc};
 
 use actix_web::{dev::Server, web::Data, App, HttpServer};
+use secrecy::Secret;
 use tracing_actix_web::TracingLogger;
 
 use crate::{
     routes::{
-        create_bom, create_component, get_all_boms, get_bom_by_id, get_bom_diff, get_bom_version,
-        get_component_by_id, get_components, health_check, revert_bom_to_version,
-        search_components, update_bom,
+        apply_diff, archive_bom, check_snapshot_consistency, cherry_pick, clone_bom, compare_boms,
+        create_bom, create_component, delete_bom, get_all_boms, get_bom_blame, get_bom_by_id,
+        get_bom_diff, get_bom_lineage, get_bom_version, get_bom_versions, get_component_by_id,
+        get_components, health_check, lint_bom, patch_bom, preview_bom_update, purge_bom,
+        purge_expired_boms, restore_bom, revert_bom_to_version, search_components,
+        set_component_lifecycle, squash_bom_versions, tag_bom_version, unarchive_bom, update_bom,
+        upgrade_event_schema, AdminToken,
     },
     services::bom_service::BomService,
 };
 
-pub fn run(listener: TcpListener, bom_service: Arc<BomService>) -> Result<Server, std::io::Error> {
+pub fn run(
+    listener: TcpListener,
+    bom_service: Arc<BomService>,
+    admin_token: Option<Secret<String>>,
+) -> Result<Server, std::io::Error> {
+    let admin_token = Data::new(AdminToken(admin_token));
     let server = HttpServer::new(move || {
         App::new()
             .wrap(TracingLogger::default())
@@ -22,13 +32,35 @@ pub fn run(listener: TcpListener, bom_service: Arc<BomService>) -> Result<Server
             .service(get_component_by_id)
             .service(get_components)
             .service(create_component)
+            .service(set_component_lifecycle)
             .service(get_bom_by_id)
             .service(create_bom)
             .service(update_bom)
+            .service(patch_bom)
+            .service(preview_bom_update)
             .service(get_bom_diff)
             .service(get_bom_version)
             .service(revert_bom_to_version)
+            .service(check_snapshot_consistency)
+            .service(compare_boms)
+            .service(get_bom_versions)
+            .service(get_bom_blame)
+            .service(lint_bom)
+            .service(cherry_pick)
+            .service(apply_diff)
+            .service(clone_bom)
+            .service(get_bom_lineage)
+            .service(tag_bom_version)
+            .service(squash_bom_versions)
+            .service(archive_bom)
+            .service(unarchive_bom)
+            .service(delete_bom)
+            .service(restore_bom)
+            .service(purge_bom)
+            .service(purge_expired_boms)
+            .service(upgrade_event_schema)
             .app_data(Data::from(bom_service.clone()))
+            .app_data(admin_token.clone())
     })
     .listen(listener)?
     .run();
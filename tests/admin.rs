@@ -0,0 +1,324 @@
+// BOMs are posted with `&vec![..]` throughout
+#![allow(clippy::useless_vec)]
+
+mod helpers;
+
+use bom_version_control::domain::{
+    upcasting::CURRENT_EVENT_SCHEMA_VERSION, BOMChangeEvent, Component, SnapshotConsistency,
+    VersionHistory, VersionSummary, VersionedBOM, BOM,
+};
+use uuid::Uuid;
+
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_bom_with_versions(app: &TestApp, first: &Component, second: &Component) -> Uuid {
+    let bom = app
+        .post_bom(&vec![first.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    for events in [
+        vec![BOMChangeEvent::ComponentUpdated(first.id, 2)],
+        vec![BOMChangeEvent::ComponentUpdated(first.id, 3)],
+        vec![BOMChangeEvent::ComponentAdded(second.clone(), 1)],
+        vec![BOMChangeEvent::ComponentUpdated(first.id, 4)],
+    ] {
+        app.update_bom(bom.id, events).await;
+    }
+
+    bom.id
+}
+
+async fn get_version(app: &TestApp, bom_id: Uuid, version: i32) -> reqwest::Response {
+    app.client
+        .get(format!(
+            "{}/boms/{}/?version={}",
+            &app.addr, bom_id, version
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom version request")
+}
+
+#[tokio::test]
+async fn squash_bom_versions_folds_range_into_last_version() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let first: Component = app
+        .post_component("first".to_string(), "PRT-1".to_string())
+        .await;
+    let second: Component = app
+        .post_component("second".to_string(), "PRT-2".to_string())
+        .await;
+
+    let bom_id = create_bom_with_versions(&app, &first, &second).await;
+
+    let version_four = get_version(&app, bom_id, 4)
+        .await
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/admin/boms/{}/squash", &app.addr, bom_id))
+        .bearer_auth(&app.admin_token)
+        .json(&serde_json::json!({ "from": 2, "to": 4 }))
+        .send()
+        .await
+        .expect("Failed to execute squash request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let squashed = response
+        .json::<VersionSummary>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(squashed.version, 4);
+    assert_eq!(squashed.squashed_from, Some(2));
+    assert_eq!(squashed.lines_updated, 1);
+    assert_eq!(squashed.lines_added, 1);
+
+    let history = app
+        .client
+        .get(format!("{}/boms/{}/versions", &app.addr, bom_id))
+        .send()
+        .await
+        .expect("Failed to execute get bom versions request")
+        .json::<VersionHistory>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(
+        history
+            .versions
+            .iter()
+            .map(|v| v.version)
+            .collect::<Vec<_>>(),
+        vec![5, 4, 1]
+    );
+
+    let reconstructed = get_version(&app, bom_id, 4)
+        .await
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(
+        reconstructed.bom.components.len(),
+        version_four.bom.components.len()
+    );
+    for line in version_four.bom.components.iter() {
+        assert!(reconstructed.bom.components.contains(line));
+    }
+
+    assert_eq!(get_version(&app, bom_id, 3).await.status().as_u16(), 400);
+
+    let current = app
+        .bom_service
+        .find_bom_by_id(bom_id)
+        .expect("Failed to find bom");
+    assert_eq!(current.version, 5);
+
+    let consistency = app
+        .client
+        .get(format!(
+            "{}/boms/{}/snapshots/consistency",
+            &app.addr, bom_id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute consistency request")
+        .json::<SnapshotConsistency>()
+        .await
+        .expect("Failed to parse response");
+
+    assert!(consistency.consistent);
+}
+
+#[tokio::test]
+async fn squash_bom_versions_across_tagged_version_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let first: Component = app
+        .post_component("first".to_string(), "PRT-1".to_string())
+        .await;
+    let second: Component = app
+        .post_component("second".to_string(), "PRT-2".to_string())
+        .await;
+
+    let bom_id = create_bom_with_versions(&app, &first, &second).await;
+
+    let tagged = app
+        .client
+        .put(format!("{}/boms/{}/versions/3/tag", &app.addr, bom_id))
+        .json(&serde_json::json!({ "tag": "release-1" }))
+        .send()
+        .await
+        .expect("Failed to execute tag request");
+
+    assert_eq!(tagged.status().as_u16(), 200);
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/admin/boms/{}/squash", &app.addr, bom_id))
+        .bearer_auth(&app.admin_token)
+        .json(&serde_json::json!({ "from": 2, "to": 4 }))
+        .send()
+        .await
+        .expect("Failed to execute squash request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    let history = app
+        .client
+        .get(format!("{}/boms/{}/versions", &app.addr, bom_id))
+        .send()
+        .await
+        .expect("Failed to execute get bom versions request")
+        .json::<VersionHistory>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(history.total, 5);
+}
+
+#[tokio::test]
+async fn purge_bom_removes_deleted_bom_permanently() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "PRT-1".to_string())
+        .await;
+
+    let bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let purge = || {
+        app.client
+            .delete(format!("{}/admin/boms/{}", &app.addr, bom.id))
+            .bearer_auth(&app.admin_token)
+            .send()
+    };
+
+    // Act
+    let purge_before_delete = purge().await.expect("Failed to execute purge request");
+
+    app.client
+        .delete(format!("{}/boms/{}", &app.addr, bom.id))
+        .send()
+        .await
+        .expect("Failed to execute delete request");
+
+    let purge_after_delete = purge().await.expect("Failed to execute purge request");
+
+    // Assert
+    assert_eq!(purge_before_delete.status().as_u16(), 404);
+    assert_eq!(purge_after_delete.status().as_u16(), 204);
+
+    let restore = app
+        .client
+        .post(format!("{}/boms/{}/restore", &app.addr, bom.id))
+        .send()
+        .await
+        .expect("Failed to execute restore request");
+
+    assert_eq!(restore.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn upgrade_event_schema_leaves_versions_readable() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let first: Component = app
+        .post_component("name".to_string(), "PRT-1".to_string())
+        .await;
+    let second: Component = app
+        .post_component("name".to_string(), "PRT-2".to_string())
+        .await;
+
+    let bom_id = create_bom_with_versions(&app, &first, &second).await;
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/admin/events/upgrade", &app.addr))
+        .bearer_auth(&app.admin_token)
+        .send()
+        .await
+        .expect("Failed to execute upgrade request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let upgrade = response
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(
+        upgrade["schema_version"],
+        serde_json::json!(CURRENT_EVENT_SCHEMA_VERSION)
+    );
+    assert!(upgrade["snapshots_upgraded"].is_u64());
+
+    let version = get_version(&app, bom_id, 3)
+        .await
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(version.bom.components[0].quantity, 3);
+}
+
+#[tokio::test]
+async fn admin_routes_require_admin_token() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let bom_id = Uuid::new_v4();
+    let requests = || {
+        [
+            app.client
+                .post(format!("{}/admin/boms/{}/squash", &app.addr, bom_id))
+                .json(&serde_json::json!({ "from": 1, "to": 2 })),
+            app.client
+                .delete(format!("{}/admin/boms/{}", &app.addr, bom_id)),
+            app.client.post(format!("{}/admin/boms/purge", &app.addr)),
+            app.client
+                .post(format!("{}/admin/events/upgrade", &app.addr)),
+        ]
+    };
+
+    for (without_token, wrong_token) in requests().into_iter().zip(requests()) {
+        // Act
+        let without_token = without_token
+            .send()
+            .await
+            .expect("Failed to execute admin request");
+        let wrong_token = wrong_token
+            .bearer_auth("not-the-admin-token")
+            .send()
+            .await
+            .expect("Failed to execute admin request");
+
+        // Assert
+        assert_eq!(without_token.status().as_u16(), 401);
+        assert_eq!(wrong_token.status().as_u16(), 401);
+    }
+}
@@ -1,10 +1,24 @@
+// BOMs are posted with `&vec![..]` throughout
+#![allow(clippy::useless_vec)]
+
 mod helpers;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use bom_version_control::domain::{
-    newtypes::new_bom::NewBOM, BOMChangeEvent, BOMDiff, Component, CountedComponent, PartialDiff,
-    Price, BOM,
+use bom_version_control::{
+    domain::{
+        newtypes::{
+            bom_update::BOMUpdate,
+            change_request::{BOMChangeRequest, ComponentReference},
+            idempotency_key::IdempotencyKey,
+            new_bom::NewBOM,
+        },
+        BOMBlame, BOMChangeEvent, BOMDiff, BOMLineage, BOMLint, BOMPreview, BomVersion, Component,
+        CountedComponent, LintCheck, PartialDiff, Severity, SnapshotConsistency, VersionHistory,
+        VersionMetadata, VersionSummary, VersionedBOM, BOM,
+    },
+    infrastructure::{error::DatabaseError, models::bom_version::BomVersion as DbBomVersion},
+    services::error::ServiceError,
 };
 use uuid::Uuid;
 
@@ -24,6 +38,7 @@ async fn create_bom_returns_created() {
 
     // Assert
     assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(response.headers()["ETag"], "\"1\"");
 }
 
 #[tokio::test]
@@ -31,27 +46,17 @@ async fn create_bom_returns_bad_request() {
     // Arrange
     let app = spawn_app().await;
 
-    let comp: Component = Component {
-        id: Uuid::new_v4(),
-        name: "abcde".to_string(),
-        part_number: "123456".to_string(),
-        description: None,
-        supplier: "supplier".to_string(),
-        price: Price {
-            value: 100.0,
-            currency: "USD".to_string(),
-        },
-    };
+    let comp: Component = app
+        .post_component("abcde".to_string(), "123456".to_string())
+        .await;
 
     let event = BOMChangeEvent::ComponentAdded(comp, 1);
 
     // Act
     let response = app
         .client
-        .post(&format!("{}/boms", &app.addr))
-        .json(&NewBOM {
-            events: vec![event],
-        })
+        .post(format!("{}/boms", &app.addr))
+        .json(&NewBOM::new(vec![event]))
         .send()
         .await
         .expect("Failed to execute create bom request");
@@ -79,7 +84,7 @@ async fn get_bom_by_id_returns_correct_bom() {
     // Act
     let response = app
         .client
-        .get(&format!("{}/boms/{}", &app.addr, added_bom.id))
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
         .send()
         .await
         .expect("Failed to execute get bom request");
@@ -103,7 +108,7 @@ async fn get_bom_by_id_with_invalid_id_returns_not_found() {
     // Act
     let response = app
         .client
-        .get(&format!("{}/boms/{}", &app.addr, Uuid::new_v4()))
+        .get(format!("{}/boms/{}", &app.addr, Uuid::new_v4()))
         .send()
         .await
         .expect("Failed to execute get bom request");
@@ -131,7 +136,7 @@ async fn update_bom_with_invalid_input_returns_bad_request() {
     // Act
     let response = app
         .client
-        .put(&format!("{}/boms/{}", &app.addr, added_bom.id))
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
         .send()
         .await
         .expect("Failed to execute update bom request");
@@ -159,11 +164,11 @@ async fn update_bom_returns_created() {
     // Act
     let response = app
         .client
-        .put(&format!("{}/boms/{}", &app.addr, added_bom.id))
-        .json(&vec![
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![
             BOMChangeEvent::ComponentUpdated(comp.id, 2),
             BOMChangeEvent::NameChanged("UpdatedName".to_string()),
-        ])
+        ]))
         .send()
         .await
         .expect("Failed to execute update bom request");
@@ -190,11 +195,11 @@ async fn update_bom_archives_old_bom() {
 
     // Act
     app.client
-        .put(&format!("{}/boms/{}", &app.addr, added_bom.id))
-        .json(&vec![
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![
             BOMChangeEvent::ComponentUpdated(comp.id, 2),
             BOMChangeEvent::NameChanged("UpdatedName".to_string()),
-        ])
+        ]))
         .send()
         .await
         .expect("Failed to execute update bom request");
@@ -202,7 +207,8 @@ async fn update_bom_archives_old_bom() {
     let old_bom: BOM = app
         .bom_service
         .find_bom_by_version_and_id(added_bom.id, 1)
-        .expect("Failed to find bom by version and id");
+        .expect("Failed to find bom by version and id")
+        .bom;
 
     let expected_version = 1;
     let expected_bom_id = added_bom.id;
@@ -218,10 +224,51 @@ async fn get_bom_diff_returns_with_invalid_version_range_bad_request() {
     // Arrange
     let app = spawn_app().await;
 
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .get(format!(
+            "{}/boms/{}/diffs?from=1&to=3",
+            &app.addr, added_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom diffs request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+
+    let body = response
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(
+        body["error"],
+        "Bad Request: Version 3 is out of range. Available versions are 1 to 1"
+    );
+}
+
+#[tokio::test]
+async fn get_bom_diff_for_unknown_bom_returns_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+
     // Act
     let response = app
         .client
-        .get(&format!(
+        .get(format!(
             "{}/boms/{}/diffs?from=1&to=1",
             &app.addr,
             Uuid::new_v4()
@@ -231,7 +278,84 @@ async fn get_bom_diff_returns_with_invalid_version_range_bad_request() {
         .expect("Failed to execute get bom diffs request");
 
     // Assert
-    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn get_bom_diff_in_reverse_direction_returns_correct_diffs() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+    let other_comp: Component = app
+        .post_component("other".to_string(), "other_part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    for events in [
+        vec![BOMChangeEvent::ComponentAdded(other_comp.clone(), 3)],
+        vec![
+            BOMChangeEvent::ComponentUpdated(comp.id, 2),
+            BOMChangeEvent::NameChanged("UpdatedName".to_string()),
+        ],
+    ] {
+        app.client
+            .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+            .json(&BOMUpdate::new(events))
+            .send()
+            .await
+            .expect("Failed to execute update bom request");
+    }
+
+    // Act
+    let response = app
+        .client
+        .get(format!(
+            "{}/boms/{}/diffs?from=3&to=1",
+            &app.addr, added_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom diffs request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let returned_diff = response
+        .json::<BOMDiff>()
+        .await
+        .expect("Failed to parse response");
+
+    let mut expected_components_updated = HashMap::new();
+    expected_components_updated.insert(
+        comp.id,
+        PartialDiff {
+            from: CountedComponent::new(comp.clone(), 2),
+            to: CountedComponent::new(comp.clone(), 1),
+        },
+    );
+
+    let expected_diff = BOMDiff {
+        name_changed: Some(PartialDiff {
+            from: "UpdatedName".to_string(),
+            to: "TestBom".to_string(),
+        }),
+        description_changed: None,
+        components_added: HashMap::new(),
+        components_updated: expected_components_updated,
+        components_removed: vec![other_comp],
+        components_replaced: HashMap::new(),
+    };
+
+    assert_eq!(returned_diff, expected_diff);
 }
 
 #[tokio::test]
@@ -251,11 +375,11 @@ async fn get_bom_diff_returns_correct_diffs() {
         .expect("Failed to parse response");
 
     app.client
-        .put(&format!("{}/boms/{}", &app.addr, added_bom.id))
-        .json(&vec![
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![
             BOMChangeEvent::ComponentUpdated(comp.id, 2),
             BOMChangeEvent::NameChanged("UpdatedName".to_string()),
-        ])
+        ]))
         .send()
         .await
         .expect("Failed to execute update bom request");
@@ -263,7 +387,7 @@ async fn get_bom_diff_returns_correct_diffs() {
     // Act
     let response = app
         .client
-        .get(&format!(
+        .get(format!(
             "{}/boms/{}/diffs?from=1&to=2",
             &app.addr, added_bom.id
         ))
@@ -297,6 +421,7 @@ async fn get_bom_diff_returns_correct_diffs() {
         components_added: HashMap::new(),
         components_updated: expected_components_added,
         components_removed: Vec::new(),
+        components_replaced: HashMap::new(),
     };
 
     assert_eq!(returned_diff, expected_diff);
@@ -319,11 +444,11 @@ async fn get_bom_version_returns_correct_version() {
         .expect("Failed to parse response");
 
     app.client
-        .put(&format!("{}/boms/{}", &app.addr, added_bom.id))
-        .json(&vec![
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![
             BOMChangeEvent::ComponentUpdated(comp.id, 2),
             BOMChangeEvent::NameChanged("UpdatedName".to_string()),
-        ])
+        ]))
         .send()
         .await
         .expect("Failed to execute update bom request");
@@ -331,7 +456,7 @@ async fn get_bom_version_returns_correct_version() {
     // Act
     let response = app
         .client
-        .get(&format!(
+        .get(format!(
             "{}/boms/{}/?version={}",
             &app.addr, added_bom.id, 1
         ))
@@ -367,11 +492,11 @@ async fn revert_bom_to_version_reverts_to_correct_version() {
         .expect("Failed to parse response");
 
     app.client
-        .put(&format!("{}/boms/{}", &app.addr, added_bom.id))
-        .json(&vec![
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![
             BOMChangeEvent::ComponentUpdated(comp.id, 2),
             BOMChangeEvent::NameChanged("UpdatedName".to_string()),
-        ])
+        ]))
         .send()
         .await
         .expect("Failed to execute update bom request");
@@ -379,7 +504,7 @@ async fn revert_bom_to_version_reverts_to_correct_version() {
     // Act
     let response = app
         .client
-        .put(&format!(
+        .put(format!(
             "{}/boms/{}/?revert_to_version={}",
             &app.addr, added_bom.id, 1
         ))
@@ -387,6 +512,7 @@ async fn revert_bom_to_version_reverts_to_correct_version() {
         .await
         .expect("Failed to execute revert bom request");
     assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(response.headers()["ETag"], "\"3\"");
 
     let revert = response
         .json::<BOM>()
@@ -397,7 +523,7 @@ async fn revert_bom_to_version_reverts_to_correct_version() {
     // Assert
     let reverted_bom = app
         .client
-        .get(&format!("{}/boms/{}", &app.addr, added_bom.id))
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
         .send()
         .await
         .expect("Failed to execute get bom request")
@@ -409,3 +535,2684 @@ async fn revert_bom_to_version_reverts_to_correct_version() {
     assert_eq!(reverted_bom.components, added_bom.components);
     assert_eq!(reverted_bom.version, 3);
 }
+
+#[tokio::test]
+async fn revert_bom_to_version_without_description_clears_it() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .json(&NewBOM::new(vec![
+            BOMChangeEvent::NameChanged("TestBom".to_string()),
+            BOMChangeEvent::ComponentAdded(comp, 1),
+        ]))
+        .send()
+        .await
+        .expect("Failed to execute create bom request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.update_bom(
+        added_bom.id,
+        vec![BOMChangeEvent::DescriptionChanged(
+            "Added later".to_string(),
+        )],
+    )
+    .await;
+
+    // Act
+    let response = app
+        .client
+        .put(format!(
+            "{}/boms/{}/?revert_to_version={}",
+            &app.addr, added_bom.id, 1
+        ))
+        .send()
+        .await
+        .expect("Failed to execute revert bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+
+    let reverted_bom = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get bom request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(reverted_bom.description, None);
+    assert_eq!(reverted_bom.version, 3);
+}
+
+#[tokio::test]
+async fn create_bom_records_version_metadata() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let metadata = VersionMetadata {
+        author: Some("jane.doe".to_string()),
+        message: Some("Initial draft".to_string()),
+        ticket: Some("ECO-1".to_string()),
+    };
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .json(
+            &NewBOM::new(vec![
+                BOMChangeEvent::NameChanged("TestBom".to_string()),
+                BOMChangeEvent::ComponentAdded(comp, 1),
+            ])
+            .with_metadata(metadata.clone()),
+        )
+        .send()
+        .await
+        .expect("Failed to execute create bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+
+    let created_bom = response
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(created_bom.metadata, metadata);
+
+    let first_version = app
+        .client
+        .get(format!(
+            "{}/boms/{}/?version={}",
+            &app.addr, created_bom.bom.id, 1
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom version request")
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(first_version.metadata, metadata);
+}
+
+#[tokio::test]
+async fn update_bom_records_version_metadata() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let metadata = VersionMetadata {
+        author: Some("john.doe".to_string()),
+        message: Some("Double the quantity".to_string()),
+        ticket: None,
+    };
+
+    // Act
+    let response = app
+        .client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(
+            &BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(comp.id, 2)])
+                .with_metadata(metadata.clone()),
+        )
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+
+    let updated_bom = response
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(updated_bom.metadata, metadata);
+
+    let second_version = app
+        .bom_service
+        .find_bom_by_version_and_id(added_bom.id, 2)
+        .expect("Failed to find bom by version and id");
+
+    assert_eq!(second_version.metadata, metadata);
+}
+
+#[tokio::test]
+async fn get_bom_version_reconstructs_from_snapshots() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    for qty in 2..=5 {
+        app.client
+            .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+            .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+                comp.id, qty,
+            )]))
+            .send()
+            .await
+            .expect("Failed to execute update bom request");
+    }
+
+    // Act
+    let third_version = app
+        .bom_service
+        .find_bom_by_version_and_id(added_bom.id, 3)
+        .expect("Failed to find bom by version and id")
+        .bom;
+
+    let fifth_version = app
+        .bom_service
+        .find_bom_by_version_and_id(added_bom.id, 5)
+        .expect("Failed to find bom by version and id")
+        .bom;
+
+    // Assert
+    assert_eq!(third_version.version, 3);
+    assert_eq!(
+        third_version.components,
+        vec![CountedComponent::new(comp.clone(), 3)]
+    );
+    assert_eq!(fifth_version.version, 5);
+    assert_eq!(
+        fifth_version.components,
+        vec![CountedComponent::new(comp, 5)]
+    );
+}
+
+#[tokio::test]
+async fn check_snapshot_consistency_agrees_with_full_replay() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    for qty in 2..=5 {
+        app.client
+            .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+            .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+                comp.id, qty,
+            )]))
+            .send()
+            .await
+            .expect("Failed to execute update bom request");
+    }
+
+    // Act
+    let response = app
+        .client
+        .get(format!(
+            "{}/boms/{}/snapshots/consistency",
+            &app.addr, added_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute snapshot consistency request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let report = response
+        .json::<SnapshotConsistency>()
+        .await
+        .expect("Failed to parse response");
+
+    assert!(report.consistent);
+    assert_eq!(report.checked_versions, vec![2, 4]);
+    assert!(report.mismatched_versions.is_empty());
+}
+
+#[tokio::test]
+async fn revert_bom_to_version_labels_new_version_as_revert() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+    let other_comp: Component = app
+        .post_component("other".to_string(), "other_part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![
+            BOMChangeEvent::ComponentRemoved(comp.clone()),
+            BOMChangeEvent::ComponentAdded(other_comp, 3),
+            BOMChangeEvent::NameChanged("UpdatedName".to_string()),
+        ]))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Act
+    let reverted_bom = app
+        .client
+        .put(format!(
+            "{}/boms/{}/?revert_to_version={}&author={}",
+            &app.addr, added_bom.id, 1, "jane.doe"
+        ))
+        .send()
+        .await
+        .expect("Failed to execute revert bom request")
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Assert
+    assert_eq!(reverted_bom.bom.version, 3);
+    assert_eq!(reverted_bom.bom.name, added_bom.name);
+    assert_eq!(reverted_bom.bom.components, added_bom.components);
+    assert_eq!(
+        reverted_bom.metadata,
+        VersionMetadata {
+            author: Some("jane.doe".to_string()),
+            message: Some("Revert to version 1".to_string()),
+            ticket: None,
+        }
+    );
+
+    let first_version = app
+        .bom_service
+        .find_bom_by_version_and_id(added_bom.id, 1)
+        .expect("Failed to find bom by version and id")
+        .bom;
+    let third_version = app
+        .bom_service
+        .find_bom_by_version_and_id(added_bom.id, 3)
+        .expect("Failed to find bom by version and id")
+        .bom;
+
+    assert_eq!(third_version.name, first_version.name);
+    assert_eq!(third_version.description, first_version.description);
+    assert_eq!(third_version.components, first_version.components);
+}
+
+#[tokio::test]
+async fn get_bom_versions_returns_paginated_history() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    for qty in 2..=3 {
+        app.client
+            .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+            .json(
+                &BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(comp.id, qty)])
+                    .with_metadata(VersionMetadata {
+                        author: Some("jane.doe".to_string()),
+                        ..Default::default()
+                    }),
+            )
+            .send()
+            .await
+            .expect("Failed to execute update bom request");
+    }
+
+    // Act
+    let first_page = app
+        .client
+        .get(format!(
+            "{}/boms/{}/versions?page=1&per_page=2",
+            &app.addr, added_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom versions request");
+
+    let second_page = app
+        .client
+        .get(format!(
+            "{}/boms/{}/versions?page=2&per_page=2",
+            &app.addr, added_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom versions request")
+        .json::<VersionHistory>()
+        .await
+        .expect("Failed to parse response");
+
+    // Assert
+    assert_eq!(first_page.status().as_u16(), 200);
+
+    let first_page = first_page
+        .json::<VersionHistory>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(first_page.total, 3);
+    assert_eq!(
+        first_page
+            .versions
+            .iter()
+            .map(|v| v.version)
+            .collect::<Vec<_>>(),
+        vec![3, 2]
+    );
+
+    let latest = &first_page.versions[0];
+    assert_eq!(latest.metadata.author, Some("jane.doe".to_string()));
+    assert_eq!(
+        latest.changes,
+        vec![BOMChangeEvent::ComponentUpdated(comp.id, 3)]
+    );
+    assert_eq!(
+        latest.summary,
+        vec![format!("ComponentUpdated({}, 3)", comp.id)]
+    );
+    assert_eq!(latest.lines_updated, 1);
+
+    assert_eq!(second_page.versions.len(), 1);
+    assert_eq!(second_page.versions[0].version, 1);
+    assert_eq!(second_page.versions[0].metadata.author, None);
+    assert_eq!(second_page.versions[0].lines_added, 1);
+}
+
+#[tokio::test]
+async fn get_bom_versions_with_invalid_pagination_returns_bad_request() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .get(format!(
+            "{}/boms/{}/versions?per_page=0",
+            &app.addr, added_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom versions request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn get_bom_blame_annotates_lines_with_versions() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let first: Component = app
+        .post_component("first".to_string(), "first_part_number".to_string())
+        .await;
+    let second: Component = app
+        .post_component("second".to_string(), "second_part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![first.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentAdded(
+            second.clone(),
+            1,
+        )]))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    app.client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+            first.id, 5,
+        )]))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Act
+    let current = app
+        .client
+        .get(format!("{}/boms/{}/blame", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get bom blame request");
+
+    let at_version_two = app
+        .client
+        .get(format!(
+            "{}/boms/{}/blame?version=2",
+            &app.addr, added_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom blame request")
+        .json::<BOMBlame>()
+        .await
+        .expect("Failed to parse response");
+
+    // Assert
+    assert_eq!(current.status().as_u16(), 200);
+
+    let current = current
+        .json::<BOMBlame>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(current.version, 3);
+    assert_eq!(current.name.value, added_bom.name);
+    assert_eq!(current.name.last_changed.version, 1);
+
+    let blamed = |blame: &BOMBlame, id: Uuid| {
+        let line = blame
+            .components
+            .iter()
+            .find(|line| line.line.component.id == id)
+            .expect("Line is missing from blame");
+        (
+            line.line.quantity,
+            line.introduced.version,
+            line.last_changed.version,
+        )
+    };
+
+    assert_eq!(blamed(&current, first.id), (5, 1, 3));
+    assert_eq!(blamed(&current, second.id), (1, 2, 2));
+
+    assert_eq!(at_version_two.version, 2);
+    assert_eq!(blamed(&at_version_two, first.id), (1, 1, 1));
+}
+
+#[tokio::test]
+async fn get_bom_blame_with_invalid_version_returns_bad_request() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .get(format!(
+            "{}/boms/{}/blame?version=2",
+            &app.addr, added_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom blame request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn get_bom_as_of_returns_version_in_effect_at_that_instant() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let before_creation = added_bom.created_at - chrono::Duration::seconds(1);
+
+    for qty in 2..=3 {
+        app.client
+            .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+            .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+                comp.id, qty,
+            )]))
+            .send()
+            .await
+            .expect("Failed to execute update bom request");
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let versions = app
+        .client
+        .get(format!("{}/boms/{}/versions", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get bom versions request")
+        .json::<VersionHistory>()
+        .await
+        .expect("Failed to parse response");
+
+    let second_version = versions
+        .versions
+        .iter()
+        .find(|version| version.version == 2)
+        .expect("Version 2 is missing");
+
+    // Act
+    let response = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .query(&[(
+            "as_of",
+            second_version
+                .created_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+        )])
+        .send()
+        .await
+        .expect("Failed to execute get bom request");
+
+    let before_creation_response = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .query(&[(
+            "as_of",
+            before_creation.to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+        )])
+        .send()
+        .await
+        .expect("Failed to execute get bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let bom = response
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(bom.bom.version, 2);
+    assert_eq!(bom.bom.components[0].quantity, 2);
+
+    assert_eq!(before_creation_response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn get_bom_returns_version_etag() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .headers()
+            .get("ETag")
+            .expect("ETag header is missing"),
+        "\"1\""
+    );
+}
+
+#[tokio::test]
+async fn update_bom_with_matching_if_match_succeeds() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .header("If-Match", "\"1\"")
+        .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+            comp.id, 2,
+        )]))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(
+        response
+            .headers()
+            .get("ETag")
+            .expect("ETag header is missing"),
+        "\"2\""
+    );
+}
+
+#[tokio::test]
+async fn update_bom_with_stale_version_returns_conflict() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(
+            &BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(comp.id, 2)])
+                .expecting_version(1),
+        )
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Act
+    let stale_body = app
+        .client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(
+            &BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(comp.id, 3)])
+                .expecting_version(1),
+        )
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    let stale_header = app
+        .client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .header("If-Match", "\"1\"")
+        .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+            comp.id, 3,
+        )]))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Assert
+    assert_eq!(stale_body.status().as_u16(), 409);
+    assert_eq!(stale_header.status().as_u16(), 409);
+
+    let body = stale_header
+        .json::<HashMap<String, serde_json::Value>>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(body.get("current_version"), Some(&serde_json::json!(2)));
+
+    let bom = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get bom request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(bom.version, 2);
+    assert_eq!(bom.components[0].quantity, 2);
+}
+
+#[tokio::test]
+async fn concurrent_updates_produce_consecutive_versions() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let requests = (2..=6)
+        .map(|qty| {
+            let request = app
+                .client
+                .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+                .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+                    comp.id, qty,
+                )]))
+                .send();
+            tokio::spawn(request)
+        })
+        .collect::<Vec<_>>();
+
+    let mut statuses = vec![];
+    for request in requests {
+        let response = request
+            .await
+            .expect("Update task panicked")
+            .expect("Failed to execute update bom request");
+        statuses.push(response.status().as_u16());
+    }
+
+    // Assert
+    assert!(statuses.iter().all(|status| *status == 201));
+
+    let history = app
+        .client
+        .get(format!("{}/boms/{}/versions", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get bom versions request")
+        .json::<VersionHistory>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(history.total, 6);
+    assert_eq!(
+        history
+            .versions
+            .iter()
+            .map(|v| v.version)
+            .collect::<Vec<_>>(),
+        (1..=6).rev().collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn create_bom_with_repeated_idempotency_key_returns_original_bom() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let key = Uuid::new_v4().to_string();
+    let new_bom = NewBOM::new(vec![
+        BOMChangeEvent::NameChanged("IdempotentBom".to_string()),
+        BOMChangeEvent::ComponentAdded(comp, 1),
+    ]);
+
+    let first = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .header("Idempotency-Key", &key)
+        .json(&new_bom)
+        .send()
+        .await
+        .expect("Failed to execute create bom request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let replay = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .header("Idempotency-Key", &key)
+        .json(&new_bom)
+        .send()
+        .await
+        .expect("Failed to execute create bom request");
+
+    // Assert
+    assert_eq!(replay.status().as_u16(), 201);
+    assert_eq!(
+        replay
+            .headers()
+            .get("Idempotent-Replayed")
+            .expect("Idempotent-Replayed header is missing"),
+        "true"
+    );
+
+    let replay = replay
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(replay.id, first.id);
+}
+
+#[tokio::test]
+async fn update_bom_with_repeated_idempotency_key_applies_events_once() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let key = Uuid::new_v4().to_string();
+    let update = BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(comp.id, 4)]);
+
+    // Act
+    let mut responses = vec![];
+    for _ in 0..2 {
+        responses.push(
+            app.client
+                .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+                .header("Idempotency-Key", &key)
+                .json(&update)
+                .send()
+                .await
+                .expect("Failed to execute update bom request")
+                .json::<BOM>()
+                .await
+                .expect("Failed to parse response"),
+        );
+    }
+
+    // Assert
+    assert_eq!(responses[0], responses[1]);
+    assert_eq!(responses[1].version, 2);
+
+    let bom = app
+        .bom_service
+        .find_bom_by_id(added_bom.id)
+        .expect("Failed to find bom");
+
+    assert_eq!(bom.version, 2);
+}
+
+#[tokio::test]
+async fn reusing_idempotency_key_with_different_payload_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let key = Uuid::new_v4().to_string();
+
+    app.client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .header("Idempotency-Key", &key)
+        .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+            comp.id, 4,
+        )]))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Act
+    let response = app
+        .client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .header("Idempotency-Key", &key)
+        .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+            comp.id, 5,
+        )]))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn abandoned_idempotency_key_is_taken_over_after_timeout() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let new_bom = NewBOM::new(vec![
+        BOMChangeEvent::NameChanged("TestBom".to_string()),
+        BOMChangeEvent::ComponentAdded(comp, 1),
+    ]);
+    let key = IdempotencyKey::new(&Uuid::new_v4().to_string(), "POST /boms", &new_bom)
+        .expect("Failed to create idempotency key");
+
+    // Act
+    // The outer write holds the key without storing a response while retries
+    // come in, like a request whose process died
+    let mut while_reserved = None;
+    let mut after_timeout = None;
+
+    app.bom_service
+        .write_idempotently(Some(key.clone()), || {
+            while_reserved = Some(
+                app.bom_service
+                    .write_idempotently(Some(key.clone()), || unreachable!()),
+            );
+
+            std::thread::sleep(std::time::Duration::from_millis(1500));
+
+            let retry = app
+                .bom_service
+                .write_idempotently(Some(key.clone()), || app.bom_service.insert_bom(new_bom));
+            let bom = retry.as_ref().map(|write| write.bom.clone()).map_err(|_| {
+                ServiceError::InvalidData("Retry did not take over the key".to_string())
+            });
+            after_timeout = Some(retry);
+            bom
+        })
+        .expect("Failed to write");
+
+    // Assert
+    assert!(matches!(
+        while_reserved,
+        Some(Err(ServiceError::RequestInProgress(_)))
+    ));
+
+    let retry = after_timeout
+        .expect("Retry was not attempted")
+        .expect("Retry failed");
+    assert!(!retry.replayed);
+
+    let replay = app
+        .bom_service
+        .write_idempotently(Some(key), || unreachable!())
+        .expect("Failed to replay");
+    assert!(replay.replayed);
+    assert_eq!(replay.bom, retry.bom);
+}
+
+#[tokio::test]
+async fn tag_bom_version_labels_version_once_per_bom() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.update_bom(
+        added_bom.id,
+        vec![BOMChangeEvent::ComponentUpdated(comp.id, 2)],
+    )
+    .await;
+
+    // Act
+    let tagged = app
+        .client
+        .put(format!(
+            "{}/boms/{}/versions/1/tag",
+            &app.addr, added_bom.id
+        ))
+        .json(&serde_json::json!({ "tag": "release-1" }))
+        .send()
+        .await
+        .expect("Failed to execute tag request");
+
+    let duplicate = app
+        .client
+        .put(format!(
+            "{}/boms/{}/versions/2/tag",
+            &app.addr, added_bom.id
+        ))
+        .json(&serde_json::json!({ "tag": "release-1" }))
+        .send()
+        .await
+        .expect("Failed to execute tag request");
+
+    // Assert
+    assert_eq!(tagged.status().as_u16(), 200);
+
+    let tagged = tagged
+        .json::<VersionSummary>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(tagged.version, 1);
+    assert_eq!(tagged.tag, Some("release-1".to_string()));
+
+    assert_eq!(duplicate.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn cherry_pick_applies_source_version_to_target() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let shared: Component = app
+        .post_component("shared".to_string(), "PRT-1".to_string())
+        .await;
+    let fix: Component = app
+        .post_component("fix".to_string(), "PRT-2".to_string())
+        .await;
+
+    let source = app
+        .post_bom(&vec![shared.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+    let target = app
+        .post_bom(&vec![shared.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.update_bom(
+        source.id,
+        vec![
+            BOMChangeEvent::ComponentAdded(fix.clone(), 2),
+            BOMChangeEvent::ComponentUpdated(shared.id, 3),
+        ],
+    )
+    .await;
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/boms/{}/cherry-pick", &app.addr, target.id))
+        .json(&serde_json::json!({ "source_bom_id": source.id, "version": 2 }))
+        .send()
+        .await
+        .expect("Failed to execute cherry-pick request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+
+    let picked = response
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(picked.bom.id, target.id);
+    assert_eq!(picked.bom.version, 2);
+    assert!(picked
+        .bom
+        .components
+        .contains(&CountedComponent::new(fix, 2)));
+    assert!(picked
+        .bom
+        .components
+        .contains(&CountedComponent::new(shared, 3)));
+    assert_eq!(
+        picked.metadata.message,
+        Some(format!("Cherry-pick of version 2 of BOM {}", source.id))
+    );
+}
+
+#[tokio::test]
+async fn cherry_pick_keeps_header_of_target_unless_asked_for() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let shared: Component = app
+        .post_component("shared".to_string(), "PRT-1".to_string())
+        .await;
+
+    let source = app
+        .post_bom(&vec![shared.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+    let target = app
+        .post_bom(&vec![shared.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.update_bom(
+        source.id,
+        vec![
+            BOMChangeEvent::NameChanged("Source".to_string()),
+            BOMChangeEvent::DescriptionChanged("Source description".to_string()),
+            BOMChangeEvent::ComponentUpdated(shared.id, 3),
+        ],
+    )
+    .await;
+
+    let cherry_pick = |include_header_changes: bool| {
+        app.client
+            .post(format!("{}/boms/{}/cherry-pick", &app.addr, target.id))
+            .json(&serde_json::json!({
+                "source_bom_id": source.id,
+                "version": 2,
+                "include_header_changes": include_header_changes,
+            }))
+            .send()
+    };
+
+    // Act
+    let lines_only = cherry_pick(false)
+        .await
+        .expect("Failed to execute cherry-pick request")
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let with_header = cherry_pick(true)
+        .await
+        .expect("Failed to execute cherry-pick request")
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Assert
+    assert_eq!(lines_only.bom.name, target.name);
+    assert_eq!(lines_only.bom.description, target.description);
+    assert!(lines_only
+        .bom
+        .components
+        .contains(&CountedComponent::new(shared, 3)));
+
+    assert_eq!(with_header.bom.name, "Source");
+    assert_eq!(
+        with_header.bom.description,
+        Some("Source description".to_string())
+    );
+}
+
+#[tokio::test]
+async fn cherry_pick_reports_events_that_do_not_apply() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let shared: Component = app
+        .post_component("shared".to_string(), "PRT-1".to_string())
+        .await;
+    let source_only: Component = app
+        .post_component("source only".to_string(), "PRT-2".to_string())
+        .await;
+
+    let source = app
+        .post_bom(&vec![shared.clone(), source_only.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+    let target = app
+        .post_bom(&vec![shared.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.update_bom(
+        source.id,
+        vec![
+            BOMChangeEvent::ComponentUpdated(shared.id, 3),
+            BOMChangeEvent::ComponentUpdated(source_only.id, 5),
+        ],
+    )
+    .await;
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/boms/{}/cherry-pick", &app.addr, target.id))
+        .json(&serde_json::json!({ "source_bom_id": source.id, "version": 2 }))
+        .send()
+        .await
+        .expect("Failed to execute cherry-pick request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+
+    let body = response
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+
+    let rejected = body["rejected_events"]
+        .as_array()
+        .expect("rejected_events is missing");
+
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0]["index"], 1);
+
+    let target = app
+        .bom_service
+        .find_bom_by_id(target.id)
+        .expect("Failed to find bom");
+
+    assert_eq!(target.version, 1);
+}
+
+#[tokio::test]
+async fn clone_bom_copies_version_and_records_lineage() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let original = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.update_bom(
+        original.id,
+        vec![BOMChangeEvent::ComponentUpdated(comp.id, 7)],
+    )
+    .await;
+
+    // Act
+    let response = app
+        .client
+        .post(format!(
+            "{}/boms/{}/clone?version=1&name=Variant",
+            &app.addr, original.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute clone request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(response.headers()["ETag"], "\"1\"");
+
+    let clone = response
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_ne!(clone.bom.id, original.id);
+    assert_eq!(clone.bom.name, "Variant");
+    assert_eq!(clone.bom.version, 1);
+    assert_eq!(clone.bom.description, original.description);
+    assert_eq!(clone.bom.components, vec![CountedComponent::new(comp, 1)]);
+
+    let grandchild = app
+        .client
+        .post(format!("{}/boms/{}/clone", &app.addr, clone.bom.id))
+        .send()
+        .await
+        .expect("Failed to execute clone request")
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let lineage = app
+        .client
+        .get(format!("{}/boms/{}/lineage", &app.addr, clone.bom.id))
+        .send()
+        .await
+        .expect("Failed to execute lineage request")
+        .json::<BOMLineage>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(lineage.ancestors.len(), 1);
+    assert_eq!(lineage.ancestors[0].source_bom_id, original.id);
+    assert_eq!(lineage.ancestors[0].source_version, 1);
+    assert_eq!(lineage.descendants.len(), 1);
+    assert_eq!(lineage.descendants[0].bom_id, grandchild.bom.id);
+
+    let root_lineage = app
+        .client
+        .get(format!("{}/boms/{}/lineage", &app.addr, original.id))
+        .send()
+        .await
+        .expect("Failed to execute lineage request")
+        .json::<BOMLineage>()
+        .await
+        .expect("Failed to parse response");
+
+    assert!(root_lineage.ancestors.is_empty());
+    assert_eq!(
+        root_lineage
+            .descendants
+            .iter()
+            .map(|link| link.bom_id)
+            .collect::<Vec<_>>(),
+        vec![clone.bom.id, grandchild.bom.id]
+    );
+}
+
+#[tokio::test]
+async fn archived_bom_is_hidden_from_listing_and_rejects_updates() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let archived = app
+        .client
+        .post(format!("{}/boms/{}/archive", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute archive request");
+
+    // Assert
+    assert_eq!(archived.status().as_u16(), 200);
+
+    let listed = |include_archived: bool| {
+        let app = &app;
+        async move {
+            app.client
+                .get(format!(
+                    "{}/boms?include_archived={}",
+                    &app.addr, include_archived
+                ))
+                .send()
+                .await
+                .expect("Failed to execute get boms request")
+                .json::<Vec<BOM>>()
+                .await
+                .expect("Failed to parse response")
+                .iter()
+                .any(|bom| bom.id == added_bom.id)
+        }
+    };
+
+    assert!(!listed(false).await);
+    assert!(listed(true).await);
+
+    let bom = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get bom request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert!(bom.archived_at.is_some());
+
+    let update = app
+        .update_bom(
+            added_bom.id,
+            vec![BOMChangeEvent::ComponentUpdated(comp.id, 2)],
+        )
+        .await;
+
+    assert_eq!(update.status().as_u16(), 409);
+
+    app.client
+        .post(format!("{}/boms/{}/unarchive", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute unarchive request");
+
+    let update = app
+        .update_bom(
+            added_bom.id,
+            vec![BOMChangeEvent::ComponentUpdated(comp.id, 2)],
+        )
+        .await;
+
+    assert_eq!(update.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn write_based_on_a_read_from_before_archiving_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let (mut stale, _) = app
+        .repo
+        .find_by_id(added_bom.id)
+        .expect("Failed to read bom");
+
+    app.bom_service
+        .archive_bom(added_bom.id)
+        .expect("Failed to archive bom");
+
+    // Act
+    stale.name = "Renamed".to_string();
+    stale.version += 1;
+    let new_version: DbBomVersion = BomVersion::new(
+        &added_bom.id,
+        stale.version,
+        Box::new(vec![BOMChangeEvent::NameChanged("Renamed".to_string())]),
+        VersionMetadata::default(),
+    )
+    .try_into()
+    .expect("Failed to convert version");
+
+    let written = app.repo.update_and_archive(
+        added_bom.id,
+        added_bom.version,
+        &stale,
+        &[],
+        &new_version,
+        None,
+    );
+
+    // Assert
+    assert!(matches!(written, Err(DatabaseError::Archived(id)) if id == added_bom.id));
+
+    let bom = app
+        .bom_service
+        .find_bom_by_id(added_bom.id)
+        .expect("Failed to find bom");
+
+    assert!(bom.archived_at.is_some());
+    assert_eq!(bom.version, added_bom.version);
+    assert_eq!(bom.name, added_bom.name);
+}
+
+#[tokio::test]
+async fn deleted_bom_is_not_found_until_restored() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let deleted = app
+        .client
+        .delete(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute delete request");
+
+    // Assert
+    assert_eq!(deleted.status().as_u16(), 204);
+
+    let get_bom = || {
+        app.client
+            .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+            .send()
+    };
+
+    assert_eq!(
+        get_bom()
+            .await
+            .expect("Failed to execute get bom request")
+            .status()
+            .as_u16(),
+        404
+    );
+
+    let restored = app
+        .client
+        .post(format!("{}/boms/{}/restore", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute restore request");
+
+    assert_eq!(restored.status().as_u16(), 200);
+    assert_eq!(
+        get_bom()
+            .await
+            .expect("Failed to execute get bom request")
+            .status()
+            .as_u16(),
+        200
+    );
+}
+
+#[tokio::test]
+async fn create_bom_resolves_component_references_against_catalog() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let part_number = Uuid::new_v4().to_string();
+    let comp: Component = app
+        .post_component("name".to_string(), part_number.clone())
+        .await;
+
+    let mut tampered = comp.clone();
+    tampered.price.value = 0.01;
+
+    let events = vec![
+        BOMChangeRequest::NameChanged("BOM".to_string()),
+        BOMChangeEvent::ComponentAdded(tampered, 1).into(),
+    ];
+
+    // Act
+    let by_id = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .json(&NewBOM::new(events))
+        .send()
+        .await
+        .expect("Failed to execute create bom request");
+
+    let by_part_number = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .json(&serde_json::json!({
+            "events": [
+                { "type": "name_changed", "data": "BOM" },
+                { "type": "component_added", "data": [{ "part_number": part_number }, 2] },
+            ]
+        }))
+        .send()
+        .await
+        .expect("Failed to execute create bom request");
+
+    // Assert
+    assert_eq!(by_id.status().as_u16(), 201);
+    assert_eq!(by_part_number.status().as_u16(), 201);
+
+    let by_id = by_id.json::<BOM>().await.expect("Failed to parse response");
+    let by_part_number = by_part_number
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(by_id.components[0].component, comp);
+    assert_eq!(by_part_number.components[0].component, comp);
+    assert_eq!(by_part_number.components[0].quantity, 2);
+
+    let first_version = app
+        .client
+        .get(format!("{}/boms/{}/versions", &app.addr, by_id.id))
+        .send()
+        .await
+        .expect("Failed to execute get versions request")
+        .json::<VersionHistory>()
+        .await
+        .expect("Failed to parse response");
+
+    assert!(first_version.versions[0]
+        .changes
+        .contains(&BOMChangeEvent::ComponentAdded(comp, 1)));
+}
+
+#[tokio::test]
+async fn update_bom_with_unknown_component_returns_unprocessable_entity() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&serde_json::json!({
+            "events": [
+                { "type": "component_added", "data": [{ "id": Uuid::new_v4() }, 1] },
+                { "type": "component_removed", "data": { "part_number": Uuid::new_v4() } },
+            ]
+        }))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn update_bom_with_invalid_events_stores_nothing_and_lists_every_failure() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .update_bom(
+            added_bom.id,
+            vec![
+                BOMChangeEvent::ComponentUpdated(comp.id, 0),
+                BOMChangeEvent::NameChanged("UpdatedName".to_string()),
+                BOMChangeEvent::DescriptionChanged(" ".to_string()),
+            ],
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+
+    let body = response
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+    let rejected = body["rejected_events"]
+        .as_array()
+        .expect("Missing rejected events");
+
+    assert_eq!(rejected.len(), 2);
+    assert_eq!(rejected[0]["index"], 0);
+    assert_eq!(rejected[0]["reason"], "Quantity must be greater than 0");
+    assert_eq!(rejected[1]["index"], 2);
+    assert_eq!(rejected[1]["reason"], "Invalid description input");
+
+    let bom = app
+        .bom_service
+        .find_bom_by_id(added_bom.id)
+        .expect("Failed to find bom");
+
+    assert_eq!(bom.version, 1);
+    assert_eq!(bom.name, added_bom.name);
+}
+
+#[tokio::test]
+async fn update_bom_merges_duplicate_add_into_quantity_update() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .update_bom(
+            added_bom.id,
+            vec![BOMChangeEvent::ComponentAdded(comp.clone(), 2)],
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+
+    let bom = response
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(bom.components, vec![CountedComponent::new(comp.clone(), 3)]);
+
+    let history = app
+        .client
+        .get(format!("{}/boms/{}/versions", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get versions request")
+        .json::<VersionHistory>()
+        .await
+        .expect("Failed to parse response");
+
+    let latest = history
+        .versions
+        .iter()
+        .find(|version| version.version == 2)
+        .expect("Missing version 2");
+
+    assert_eq!(
+        latest.changes,
+        vec![BOMChangeEvent::ComponentUpdated(comp.id, 3)]
+    );
+}
+
+#[tokio::test]
+async fn default_rule_set_rejects_forbidden_characters() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .update_bom(
+            added_bom.id,
+            vec![BOMChangeEvent::DescriptionChanged("<invalid>".to_string())],
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+
+    let body = response
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(body["violations"][0]["rule_id"], "forbidden-characters");
+    assert_eq!(body["violations"][0]["index"], 0);
+}
+
+#[tokio::test]
+async fn rule_set_of_bom_category_is_enforced_with_rule_ids() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let without_description = NewBOM::new(vec![
+        BOMChangeEvent::NameChanged("Infusion pump".to_string()),
+        BOMChangeEvent::ComponentAdded(comp.clone(), 1),
+    ])
+    .in_category("medical");
+
+    let with_description = NewBOM::new(vec![
+        BOMChangeEvent::NameChanged("Infusion pump".to_string()),
+        BOMChangeEvent::DescriptionChanged("Pump assembly".to_string()),
+        BOMChangeEvent::ComponentAdded(comp.clone(), 1),
+    ])
+    .in_category("medical");
+
+    // Act
+    let rejected = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .json(&without_description)
+        .send()
+        .await
+        .expect("Failed to execute create bom request");
+
+    let created = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .json(&with_description)
+        .send()
+        .await
+        .expect("Failed to execute create bom request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let too_many = app
+        .update_bom(
+            created.id,
+            vec![BOMChangeEvent::ComponentUpdated(comp.id, 101)],
+        )
+        .await;
+
+    // Assert
+    assert_eq!(rejected.status().as_u16(), 422);
+
+    let body = rejected
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(body["violations"][0]["rule_id"], "required-description");
+
+    assert_eq!(created.category.as_deref(), Some("medical"));
+
+    assert_eq!(too_many.status().as_u16(), 422);
+
+    let body = too_many
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(body["violations"][0]["rule_id"], "max-quantity");
+    assert_eq!(body["violations"][0]["index"], 0);
+}
+
+#[tokio::test]
+async fn rule_set_of_bom_without_category_is_enforced() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let new_bom = NewBOM::new(vec![
+        BOMChangeEvent::NameChanged("Pump @ v2".to_string()),
+        BOMChangeEvent::ComponentAdded(comp, 1),
+    ])
+    .with_rule_set("strict");
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/boms", &app.addr))
+        .json(&new_bom)
+        .send()
+        .await
+        .expect("Failed to execute create bom request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+
+    let body = response
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+
+    let rule_ids: Vec<&str> = body["violations"]
+        .as_array()
+        .expect("Missing violations")
+        .iter()
+        .map(|violation| violation["rule_id"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(rule_ids, vec!["allowed-characters", "required-description"]);
+}
+
+#[tokio::test]
+async fn rule_set_other_than_the_one_of_its_category_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let events = vec![
+        BOMChangeEvent::NameChanged("Infusion pump".to_string()),
+        BOMChangeEvent::DescriptionChanged("Pump assembly".to_string()),
+        BOMChangeEvent::ComponentAdded(comp, 1),
+    ];
+
+    let create = |new_bom: NewBOM| {
+        let app = &app;
+        async move {
+            app.client
+                .post(format!("{}/boms", &app.addr))
+                .json(&new_bom)
+                .send()
+                .await
+                .expect("Failed to execute create bom request")
+        }
+    };
+
+    // Act
+    let loosened = create(
+        NewBOM::new(events.clone())
+            .in_category("medical")
+            .with_rule_set("default"),
+    )
+    .await;
+
+    let unknown = create(NewBOM::new(events.clone()).with_rule_set("unknown")).await;
+
+    let matching = create(
+        NewBOM::new(events)
+            .in_category("medical")
+            .with_rule_set("medical"),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(loosened.status().as_u16(), 400);
+    assert_eq!(unknown.status().as_u16(), 400);
+    assert_eq!(matching.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn lint_bom_reports_findings_with_severity() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let obsolete: Component = app
+        .post_component("obsolete".to_string(), "PRT-1".to_string())
+        .await;
+    let active: Component = app
+        .post_component("active".to_string(), "PRT-2".to_string())
+        .await;
+
+    let lifecycle = app
+        .client
+        .put(format!(
+            "{}/components/{}/lifecycle",
+            &app.addr, obsolete.id
+        ))
+        .json(&serde_json::json!({ "status": "obsolete" }))
+        .send()
+        .await
+        .expect("Failed to execute lifecycle request");
+
+    assert_eq!(lifecycle.status().as_u16(), 200);
+
+    let added_bom = app
+        .post_bom(&vec![obsolete.clone(), active.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.update_bom(
+        added_bom.id,
+        vec![BOMChangeEvent::ComponentUpdated(active.id, 50_000)],
+    )
+    .await;
+
+    let lint = |version: Option<i32>| {
+        let app = &app;
+        async move {
+            let query = version.map_or(String::new(), |v| format!("?version={}", v));
+            app.client
+                .get(format!("{}/boms/{}/lint{}", &app.addr, added_bom.id, query))
+                .send()
+                .await
+                .expect("Failed to execute lint request")
+                .json::<BOMLint>()
+                .await
+                .expect("Failed to parse response")
+        }
+    };
+
+    // Act
+    let current = lint(None).await;
+    let first = lint(Some(1)).await;
+
+    // Assert
+    let checks = |lint: &BOMLint| {
+        lint.findings
+            .iter()
+            .map(|finding| (finding.check, finding.severity))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(current.version, 2);
+    assert_eq!(
+        checks(&current),
+        vec![
+            (LintCheck::ObsoletePart, Severity::Error),
+            (LintCheck::SuspiciousQuantity, Severity::Warning),
+            (LintCheck::SingleSupplier, Severity::Info),
+        ]
+    );
+    assert_eq!(current.findings[0].component_id, Some(obsolete.id));
+
+    assert_eq!(first.version, 1);
+    assert_eq!(
+        checks(&first),
+        vec![
+            (LintCheck::ObsoletePart, Severity::Error),
+            (LintCheck::SingleSupplier, Severity::Info),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn patch_bom_translates_json_patch_into_new_version() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+    let part_number = Uuid::new_v4().to_string();
+    let other: Component = app
+        .post_component("other".to_string(), part_number.clone())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let patch = serde_json::json!([
+        { "op": "replace", "path": "/name", "value": "Patched" },
+        { "op": "replace", "path": "/components/0/quantity", "value": 5 },
+        { "op": "add", "path": "/components/-", "value": { "component": { "part_number": part_number }, "quantity": 2 } },
+    ]);
+
+    // Act
+    let response = app
+        .client
+        .patch(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .header("Content-Type", "application/json-patch+json")
+        .header("If-Match", "\"1\"")
+        .body(patch.to_string())
+        .send()
+        .await
+        .expect("Failed to execute patch request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.headers()["ETag"], "\"2\"");
+
+    let bom = response
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(bom.name, "Patched");
+    assert_eq!(bom.version, 2);
+    assert_eq!(
+        bom.components,
+        vec![
+            CountedComponent::new(comp.clone(), 5),
+            CountedComponent::new(other.clone(), 2),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn patch_bom_records_version_metadata_from_query() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let patch = serde_json::json!([{ "op": "replace", "path": "/name", "value": "Patched" }]);
+
+    // Act
+    let response = app
+        .client
+        .patch(format!(
+            "{}/boms/{}?author=alice&message=Rename&ticket=BOM-1",
+            &app.addr, added_bom.id
+        ))
+        .header("Content-Type", "application/json-patch+json")
+        .body(patch.to_string())
+        .send()
+        .await
+        .expect("Failed to execute patch request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let patched = response
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let expected = VersionMetadata {
+        author: Some("alice".to_string()),
+        message: Some("Rename".to_string()),
+        ticket: Some("BOM-1".to_string()),
+    };
+
+    assert_eq!(patched.metadata, expected);
+
+    let stored = app
+        .client
+        .get(format!(
+            "{}/boms/{}/?version={}",
+            &app.addr, added_bom.id, 2
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get bom version request")
+        .json::<VersionedBOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(stored.metadata, expected);
+}
+
+#[tokio::test]
+async fn patch_bom_touching_read_only_fields_returns_bad_request() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    for patch in [
+        serde_json::json!([{ "op": "replace", "path": "/id", "value": Uuid::new_v4() }]),
+        serde_json::json!([{ "op": "replace", "path": "/version", "value": 7 }]),
+        serde_json::json!([{ "op": "remove", "path": "/created_at" }]),
+    ] {
+        // Act
+        let response = app
+            .client
+            .patch(format!("{}/boms/{}", &app.addr, added_bom.id))
+            .header("Content-Type", "application/json-patch+json")
+            .body(patch.to_string())
+            .send()
+            .await
+            .expect("Failed to execute patch request");
+
+        // Assert
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "Patch {} was accepted",
+            patch
+        );
+    }
+
+    let bom = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(bom.version, 1);
+}
+
+#[tokio::test]
+async fn patch_bom_with_plain_json_returns_unsupported_media_type() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .patch(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&serde_json::json!([{ "op": "replace", "path": "/name", "value": "Patched" }]))
+        .send()
+        .await
+        .expect("Failed to execute patch request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 415);
+}
+
+#[tokio::test]
+async fn preview_bom_update_reports_result_without_writing() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+    let other: Component = app
+        .post_component("other".to_string(), "other_part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/boms/{}/preview", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![
+            BOMChangeEvent::ComponentUpdated(comp.id, 3),
+            BOMChangeEvent::ComponentAdded(other.clone(), 1),
+        ]))
+        .send()
+        .await
+        .expect("Failed to execute preview request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let preview = response
+        .json::<BOMPreview>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(preview.bom.version, 2);
+    assert_eq!(
+        preview.bom.components,
+        vec![
+            CountedComponent::new(comp.clone(), 3),
+            CountedComponent::new(other.clone(), 1),
+        ]
+    );
+    assert!(preview.diff.components_added.contains_key(&other.id));
+    assert!(preview.diff.components_updated.contains_key(&comp.id));
+    assert_eq!(
+        preview.cost_delta,
+        BTreeMap::from([("EUR".to_string(), 300.0)])
+    );
+
+    let bom = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(bom.version, 1);
+    assert_eq!(bom.components, vec![CountedComponent::new(comp.clone(), 1)]);
+}
+
+#[tokio::test]
+async fn preview_bom_update_reports_rejected_events() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .post(format!("{}/boms/{}/preview", &app.addr, added_bom.id))
+        .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+            Uuid::new_v4(),
+            3,
+        )]))
+        .send()
+        .await
+        .expect("Failed to execute preview request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn apply_diff_replays_diff_of_another_bom_and_detects_conflicts() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+    let other: Component = app
+        .post_component("other".to_string(), "other_part_number".to_string())
+        .await;
+
+    let source = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+    let target = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.update_bom(
+        source.id,
+        vec![
+            BOMChangeEvent::ComponentUpdated(comp.id, 4),
+            BOMChangeEvent::ComponentAdded(other.clone(), 2),
+        ],
+    )
+    .await;
+
+    let diff = app
+        .client
+        .get(format!(
+            "{}/boms/{}/diffs?from=1&to=2",
+            &app.addr, source.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute get diff request")
+        .json::<BOMDiff>()
+        .await
+        .expect("Failed to parse response");
+
+    let apply_diff = serde_json::json!({ "diff": diff, "message": "Sync with source" });
+
+    // Act
+    let applied = app
+        .client
+        .post(format!("{}/boms/{}/apply-diff", &app.addr, target.id))
+        .json(&apply_diff)
+        .send()
+        .await
+        .expect("Failed to execute apply diff request");
+
+    let reapplied = app
+        .client
+        .post(format!("{}/boms/{}/apply-diff", &app.addr, target.id))
+        .json(&apply_diff)
+        .send()
+        .await
+        .expect("Failed to execute apply diff request");
+
+    // Assert
+    assert_eq!(applied.status().as_u16(), 201);
+
+    let bom = applied
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(bom.version, 2);
+    assert_eq!(
+        bom.components,
+        vec![
+            CountedComponent::new(comp.clone(), 4),
+            CountedComponent::new(other.clone(), 2),
+        ]
+    );
+
+    assert_eq!(reapplied.status().as_u16(), 409);
+
+    let body = reapplied
+        .json::<serde_json::Value>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(
+        body["conflicts"],
+        serde_json::json!([
+            {
+                "entry": format!("components_added.{}", other.id),
+                "message": "other_part_number is already part of the BOM",
+            },
+            {
+                "entry": format!("components_updated.{}", comp.id),
+                "message": "Expected 1 units of part_number but found 4",
+            },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn replace_component_keeps_line_position_and_shows_in_diff() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let first: Component = app
+        .post_component("first".to_string(), "part_number".to_string())
+        .await;
+    let second: Component = app
+        .post_component("second".to_string(), "part_number".to_string())
+        .await;
+    let part_number = Uuid::new_v4().to_string();
+    let replacement: Component = app
+        .post_component("replacement".to_string(), part_number.clone())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![first.clone(), second.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    let update = BOMUpdate::new(vec![BOMChangeRequest::ComponentReplaced {
+        old: ComponentReference::Id { id: first.id },
+        new: ComponentReference::PartNumber { part_number },
+        qty: 3,
+    }]);
+
+    // Act
+    let preview = app
+        .client
+        .post(format!("{}/boms/{}/preview", &app.addr, added_bom.id))
+        .json(&update)
+        .send()
+        .await
+        .expect("Failed to execute preview request")
+        .json::<BOMPreview>()
+        .await
+        .expect("Failed to parse response");
+
+    let response = app
+        .client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&update)
+        .send()
+        .await
+        .expect("Failed to execute update request");
+
+    // Assert
+    assert!(preview.diff.components_added.is_empty());
+    assert!(preview.diff.components_removed.is_empty());
+    assert_eq!(
+        preview.diff.components_replaced.get(&first.id),
+        Some(&PartialDiff {
+            from: CountedComponent::new(first.clone(), 1),
+            to: CountedComponent::new(replacement.clone(), 3),
+        })
+    );
+
+    assert_eq!(response.status().as_u16(), 201);
+
+    let bom = app
+        .client
+        .get(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .send()
+        .await
+        .expect("Failed to execute get request")
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(
+        bom.components,
+        vec![
+            CountedComponent::new(replacement.clone(), 3),
+            CountedComponent::new(second.clone(), 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn update_bom_accepts_bare_event_list() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let comp: Component = app
+        .post_component("name".to_string(), "part_number".to_string())
+        .await;
+
+    let added_bom = app
+        .post_bom(&vec![comp.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let response = app
+        .client
+        .put(format!("{}/boms/{}", &app.addr, added_bom.id))
+        .json(&vec![BOMChangeEvent::ComponentUpdated(comp.id, 4)])
+        .send()
+        .await
+        .expect("Failed to execute update request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+
+    let bom = response
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    assert_eq!(bom.components, vec![CountedComponent::new(comp.clone(), 4)]);
+}
@@ -3,6 +3,7 @@ mod helpers;
 use crate::helpers::spawn_app;
 use bom_version_control::domain::{newtypes::new_component::NewComponent, Component, Price};
 use reqwest::Client;
+use uuid::Uuid;
 
 #[tokio::test]
 async fn create_component_returns_created() {
@@ -12,7 +13,7 @@ async fn create_component_returns_created() {
 
     // Act
     let response = client
-        .post(&format!("{}/components", &app.addr))
+        .post(format!("{}/components", &app.addr))
         .json(&NewComponent::new(
             "TestName1".to_string(),
             "12345".to_string(),
@@ -38,7 +39,7 @@ async fn get_component_by_id_returns_not_found_for_nonexistent_component() {
 
     // Act
     let response = client
-        .get(&format!(
+        .get(format!(
             "{}/components/00000000-0000-0000-0000-000000000000",
             &app.addr
         ))
@@ -67,7 +68,7 @@ async fn search_components_returns_correct_components() {
 
     // Act
     let search_result: Vec<Component> = client
-        .get(&format!("{}/components/search?q=12345678", &app.addr))
+        .get(format!("{}/components/search?q=12345678", &app.addr))
         .send()
         .await
         .expect("Failed to execute request")
@@ -88,7 +89,7 @@ async fn create_component_persists_component() {
     let client = Client::new();
 
     let added_component: Component = client
-        .post(&format!("{}/components", &app.addr))
+        .post(format!("{}/components", &app.addr))
         .json(&NewComponent::new(
             "TestName1".to_string(),
             "12345".to_string(),
@@ -108,7 +109,7 @@ async fn create_component_persists_component() {
 
     // Act
     let returned_component: Component = client
-        .get(&format!("{}/components/{}", &app.addr, &added_component.id))
+        .get(format!("{}/components/{}", &app.addr, &added_component.id))
         .send()
         .await
         .expect("Failed to execute request")
@@ -131,7 +132,7 @@ async fn create_component_returns_bad_request_on_invalid_input() {
 
     // Act
     let response = client
-        .post(&format!("{}/components", &app.addr))
+        .post(format!("{}/components", &app.addr))
         .json(&EmptyStruct {})
         .send()
         .await
@@ -139,3 +140,25 @@ async fn create_component_returns_bad_request_on_invalid_input() {
 
     assert_eq!(response.status().as_u16(), 400);
 }
+
+#[tokio::test]
+async fn set_lifecycle_of_unknown_component_returns_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .put(format!(
+            "{}/components/{}/lifecycle",
+            &app.addr,
+            Uuid::new_v4()
+        ))
+        .json(&serde_json::json!({ "status": "nrnd" }))
+        .send()
+        .await
+        .expect("Failed to execute lifecycle request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
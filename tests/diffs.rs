@@ -0,0 +1,153 @@
+// BOMs are posted with `&vec![..]` throughout
+#![allow(clippy::useless_vec)]
+
+mod helpers;
+
+use bom_version_control::domain::{
+    newtypes::bom_update::BOMUpdate, BOMChangeEvent, BOMDiff, Component, CountedComponent,
+    PartialDiff, BOM,
+};
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn compare_boms_returns_diff_between_two_boms() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let shared: Component = app
+        .post_component("shared".to_string(), "PRT-1".to_string())
+        .await;
+    let left_only: Component = app
+        .post_component("left".to_string(), "PRT-2".to_string())
+        .await;
+    let right_only: Component = app
+        .post_component("right".to_string(), "PRT-3".to_string())
+        .await;
+
+    let left_bom = app
+        .post_bom(&vec![shared.clone(), left_only.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+    let right_bom = app
+        .post_bom(&vec![shared.clone(), right_only.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    app.client
+        .put(format!("{}/boms/{}", &app.addr, right_bom.id))
+        .json(&BOMUpdate::new(vec![BOMChangeEvent::ComponentUpdated(
+            shared.id, 4,
+        )]))
+        .send()
+        .await
+        .expect("Failed to execute update bom request");
+
+    // Act
+    let response = app
+        .client
+        .get(format!(
+            "{}/diffs?left={}@1&right={}",
+            &app.addr, left_bom.id, right_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute compare boms request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+
+    let diff = response
+        .json::<BOMDiff>()
+        .await
+        .expect("Failed to parse response");
+
+    assert!(diff.name_changed.is_none());
+    assert_eq!(diff.components_removed, vec![left_only]);
+    assert_eq!(
+        diff.components_added.get(&right_only.id),
+        Some(&CountedComponent::new(right_only.clone(), 1))
+    );
+    assert_eq!(
+        diff.components_updated.get(&shared.id),
+        Some(&PartialDiff {
+            from: CountedComponent::new(shared.clone(), 1),
+            to: CountedComponent::new(shared, 4),
+        })
+    );
+}
+
+#[tokio::test]
+async fn compare_boms_matches_components_by_part_number() {
+    // Arrange
+    let app = spawn_app().await;
+
+    let original: Component = app
+        .post_component("original".to_string(), "PRT-1".to_string())
+        .await;
+    let second_source: Component = app
+        .post_component("second source".to_string(), "PRT-1".to_string())
+        .await;
+
+    let left_bom = app
+        .post_bom(&vec![original.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+    let right_bom = app
+        .post_bom(&vec![second_source.clone()])
+        .await
+        .json::<BOM>()
+        .await
+        .expect("Failed to parse response");
+
+    // Act
+    let diff = app
+        .client
+        .get(format!(
+            "{}/diffs?left={}@1&right={}@1&match_by=part_number",
+            &app.addr, left_bom.id, right_bom.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute compare boms request")
+        .json::<BOMDiff>()
+        .await
+        .expect("Failed to parse response");
+
+    // Assert
+    assert!(diff.components_added.is_empty());
+    assert!(diff.components_removed.is_empty());
+    assert_eq!(
+        diff.components_updated.get(&original.id),
+        Some(&PartialDiff {
+            from: CountedComponent::new(original.clone(), 1),
+            to: CountedComponent::new(second_source, 1),
+        })
+    );
+}
+
+#[tokio::test]
+async fn compare_boms_with_invalid_reference_returns_bad_request() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .get(format!(
+            "{}/diffs?left=not-a-bom@1&right=not-a-bom@2",
+            &app.addr
+        ))
+        .send()
+        .await
+        .expect("Failed to execute compare boms request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 400);
+}
@@ -11,7 +11,7 @@ async fn health_check_works() {
 
     // Act
     let response = client
-        .get(&format!("{}/health_check", &app.addr))
+        .get(format!("{}/health_check", &app.addr))
         .send()
         .await
         .expect("Failed to execute request");
@@ -3,10 +3,13 @@ use std::{error::Error, net::TcpListener, sync::Arc};
 use bom_version_control::{
     configuration::get_config,
     domain::{
-        newtypes::{new_bom::NewBOM, new_component::NewComponent},
+        newtypes::{bom_update::BOMUpdate, new_bom::NewBOM, new_component::NewComponent},
         BOMChangeEvent, Component, Price,
     },
-    infrastructure::{aliases::DbPool, repositories::bom_repository::BomRepository},
+    infrastructure::{
+        aliases::DbPool,
+        repositories::{bom_repository::BomRepository, repository::Repository},
+    },
     services::bom_service::BomService,
     startup::run,
     telemetry::{get_subscriber, init_subscriber},
@@ -19,7 +22,7 @@ use diesel::{
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use dotenv::dotenv;
 use once_cell::sync::Lazy;
-use secrecy::ExposeSecret;
+use secrecy::{ExposeSecret, Secret};
 use uuid::Uuid;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
@@ -37,17 +40,20 @@ static TRACING: Lazy<()> = Lazy::new(|| {
     };
 });
 
+#[allow(dead_code)]
 pub struct TestApp {
     pub addr: String,
     pub bom_service: Arc<BomService>,
+    pub repo: Arc<dyn Repository>,
     pub client: reqwest::Client,
+    pub admin_token: String,
 }
 
 #[allow(dead_code)]
 impl TestApp {
     pub async fn post_component(&self, name: String, part_number: String) -> Component {
         self.client
-            .post(&format!("{}/components", self.addr))
+            .post(format!("{}/components", self.addr))
             .json(&NewComponent::new(
                 name,
                 part_number,
@@ -79,12 +85,21 @@ impl TestApp {
             .collect();
 
         self.client
-            .post(&format!("{}/boms", self.addr))
-            .json(&NewBOM { events })
+            .post(format!("{}/boms", self.addr))
+            .json(&NewBOM::new(events))
             .send()
             .await
             .expect("Failed to execute create bom request")
     }
+
+    pub async fn update_bom(&self, bom_id: Uuid, events: Vec<BOMChangeEvent>) -> reqwest::Response {
+        self.client
+            .put(format!("{}/boms/{}", self.addr, bom_id))
+            .json(&BOMUpdate::new(events))
+            .send()
+            .await
+            .expect("Failed to execute update bom request")
+    }
 }
 
 pub async fn spawn_app() -> TestApp {
@@ -95,30 +110,40 @@ pub async fn spawn_app() -> TestApp {
 
     let addr = format!("http://127.0.0.1:{}", port);
 
+    let admin_token = Uuid::new_v4().to_string();
+
     let config = {
         let mut c = get_config().expect("Failed to read configuration");
         c.db.db_name = Uuid::new_v4().to_string();
         c.app.port = port;
+        // Snapshot often so that reconstruction from snapshots is exercised
+        c.bom.snapshot_interval = 2;
+        // Let retries take over reservations quickly
+        c.bom.idempotency_reservation_timeout_seconds = 1;
+        c.app.admin_token = Some(Secret::new(admin_token.clone()));
         c
     };
 
     let pool = create_testing_pool(config.db.conn_string_without_db().expose_secret());
 
-    let repo = BomRepository::new(pool.clone());
+    let repo: Arc<dyn Repository> = Arc::new(BomRepository::new(pool.clone()));
 
-    let bom_service = Arc::new(BomService::new(Arc::new(repo)));
+    let bom_service = Arc::new(BomService::new(repo.clone(), config.bom.clone()));
 
     run_migrations(&mut pool.get().expect("Failed to get connection to db"))
         .expect("Failed to run migrations");
 
-    let server = run(listener, bom_service.clone()).expect("Failed to bind address");
+    let server =
+        run(listener, bom_service.clone(), config.app.admin_token).expect("Failed to bind address");
 
-    let _ = tokio::spawn(server);
+    tokio::spawn(server);
 
     TestApp {
         addr,
         bom_service,
+        repo,
         client: reqwest::Client::new(),
+        admin_token,
     }
 }
 